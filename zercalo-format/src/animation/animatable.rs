@@ -1,18 +1,26 @@
 use crate::scene::{Model, Scene};
 
 /// Trait that allows to wrap scene into more specific type and add animation state.
-/// Renderer need to know only final scene state and how to step the scene between frames.
+/// Renderer need to know only final scene state and how to evaluate the scene at given frame.
 pub trait Animatable {
-    /// Step scene for the next frame
-    fn animate(&mut self, frame: u32);
+    /// Set scene to the state at the given frame. The state must depend only on the frame
+    /// number and not on previous calls, so frames can be evaluated in any order. That allows
+    /// seeking, rendering single frame again and scrubbing in preview.
+    fn evaluate(&mut self, frame: u32);
 }
 
 /// We always can render static scene
 impl Animatable for Scene {
-    fn animate(&mut self, _frame: u32) {}
+    fn evaluate(&mut self, _frame: u32) {}
 }
 
 /// We always can render static model
 impl Animatable for Model {
-    fn animate(&mut self, _frame: u32) {}
+    fn evaluate(&mut self, _frame: u32) {}
+}
+
+impl<T: Animatable + ?Sized> Animatable for Box<T> {
+    fn evaluate(&mut self, frame: u32) {
+        self.as_mut().evaluate(frame)
+    }
 }
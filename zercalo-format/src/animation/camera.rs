@@ -0,0 +1,276 @@
+use super::animatable::Animatable;
+use super::cycle::{combine_all_cycles, combine_cycles, rotation_cycle, HasCycle};
+use super::spline::CatmullRom;
+use super::track::Track;
+use crate::scene::{HasBounding, HasMutCamera};
+use glam::f32::Quat;
+use glam::Vec3;
+
+/// Elevation of isometric view in radians, camera looks along diagonal of a cube
+pub const ISOMETRIC_ELEVATION: f32 = 0.6154797;
+
+/// Camera rig that orbits around a target with constant speed. Unlike [`super::RotationView`]
+/// the orbit can be around any axis and camera position is defined only by the frame.
+pub struct OrbitRig<T> {
+    pub scene: T,
+    /// Axis of rotation, it is also used as up direction of camera
+    pub axis: Vec3,
+    /// Point to orbit around, center of bounding volume if not set
+    pub target: Option<Vec3>,
+    /// Distance from camera to target
+    pub radius: f32,
+    /// Angle in radians above the plane that is perpendicular to axis
+    pub elevation: f32,
+    /// Angle in radians at frame 0. Angle 0 is the direction closest to X axis (Z axis when
+    /// orbiting around X).
+    pub start_angle: f32,
+    /// Radians per frame
+    pub speed: f32,
+}
+
+impl<T> OrbitRig<T> {
+    /// Orbit around Y axis with isometric view, at frame 0 camera looks from positive X and Z
+    pub fn new(scene: T, radius: f32, speed: f32) -> Self {
+        OrbitRig {
+            scene,
+            axis: Vec3::Y,
+            target: None,
+            radius,
+            elevation: ISOMETRIC_ELEVATION,
+            start_angle: -std::f32::consts::FRAC_PI_4,
+            speed,
+        }
+    }
+
+    pub fn with_axis(mut self, axis: Vec3) -> Self {
+        self.axis = axis.normalize();
+        self
+    }
+
+    pub fn with_target(mut self, target: Vec3) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    pub fn with_elevation(mut self, elevation: f32) -> Self {
+        self.elevation = elevation;
+        self
+    }
+
+    pub fn with_start_angle(mut self, start_angle: f32) -> Self {
+        self.start_angle = start_angle;
+        self
+    }
+
+    /// Position of camera relative to target at the frame
+    pub fn eye_offset(&self, frame: u32) -> Vec3 {
+        let axis = self.axis.normalize();
+        let reference = if axis.x.abs() < 0.9 { Vec3::X } else { Vec3::Z };
+        let zero = (reference - axis * reference.dot(axis)).normalize();
+        let angle = self.start_angle + self.speed * frame as f32;
+        let around = Quat::from_axis_angle(axis, angle).mul_vec3(zero);
+        (around * self.elevation.cos() + axis * self.elevation.sin()) * self.radius
+    }
+}
+
+impl<T: Animatable + HasMutCamera + HasBounding> Animatable for OrbitRig<T> {
+    fn evaluate(&mut self, frame: u32) {
+        self.scene.evaluate(frame);
+        let target = self
+            .target
+            .unwrap_or_else(|| self.scene.get_bounding_center());
+        let eye = target + self.eye_offset(frame);
+        let cam = self.scene.get_mut_camera();
+        cam.eye = eye;
+        cam.dir = (target - eye).normalize();
+        cam.up = self.axis.normalize();
+    }
+}
+
+/// Camera rig that moves camera along its view direction and zooms orthographic projection.
+/// Empty tracks leave camera unchanged.
+pub struct DollyRig<T> {
+    pub scene: T,
+    /// Point camera looks at, center of bounding volume if not set
+    pub target: Option<Vec3>,
+    /// Distance from camera to target
+    pub distance: Track<f32>,
+    /// Pixel size of camera, smaller size zooms in
+    pub zoom: Track<f32>,
+}
+
+impl<T> DollyRig<T> {
+    pub fn new(scene: T) -> Self {
+        DollyRig {
+            scene,
+            target: None,
+            distance: Track::new(),
+            zoom: Track::new(),
+        }
+    }
+
+    pub fn with_target(mut self, target: Vec3) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    pub fn with_distance(mut self, distance: Track<f32>) -> Self {
+        self.distance = distance;
+        self
+    }
+
+    pub fn with_zoom(mut self, zoom: Track<f32>) -> Self {
+        self.zoom = zoom;
+        self
+    }
+}
+
+impl<T: Animatable + HasMutCamera + HasBounding> Animatable for DollyRig<T> {
+    fn evaluate(&mut self, frame: u32) {
+        self.scene.evaluate(frame);
+        let target = self
+            .target
+            .unwrap_or_else(|| self.scene.get_bounding_center());
+        let distance = self.distance.sample(frame);
+        let zoom = self.zoom.sample(frame);
+        let cam = self.scene.get_mut_camera();
+        if let Some(distance) = distance {
+            cam.eye = target - cam.dir.normalize() * distance;
+        }
+        if let Some(pixel_size) = zoom {
+            cam.pixel_size = pixel_size;
+        }
+    }
+}
+
+/// Where camera of [`SplineRig`] looks
+#[derive(Clone, Debug, PartialEq)]
+pub enum LookAt {
+    /// Center of bounding volume of the scene
+    Center,
+    /// Fixed point
+    Point(Vec3),
+    /// Point that moves along its own path with the same timing as camera
+    Path(CatmullRom),
+    /// Along the camera path
+    Ahead,
+}
+
+/// Camera rig that moves camera along Catmull-Rom spline. The whole path takes `frames` frames,
+/// closed path loops after that and open one stays at the end.
+pub struct SplineRig<T> {
+    pub scene: T,
+    pub path: CatmullRom,
+    pub frames: u32,
+    pub look_at: LookAt,
+}
+
+impl<T> SplineRig<T> {
+    pub fn new(scene: T, path: CatmullRom, frames: u32, look_at: LookAt) -> Self {
+        SplineRig {
+            scene,
+            path,
+            frames,
+            look_at,
+        }
+    }
+
+    /// Position along the path in range 0 .. 1 at the frame. Open path reaches the end at the
+    /// last frame, closed one returns to the start after the last frame.
+    pub fn progress(&self, frame: u32) -> f32 {
+        if self.path.closed {
+            (frame % self.frames.max(1)) as f32 / self.frames.max(1) as f32
+        } else {
+            let last = self.frames.saturating_sub(1).max(1);
+            frame.min(last) as f32 / last as f32
+        }
+    }
+}
+
+impl<T: Animatable + HasMutCamera + HasBounding> Animatable for SplineRig<T> {
+    fn evaluate(&mut self, frame: u32) {
+        self.scene.evaluate(frame);
+        let t = self.progress(frame);
+        let eye = self.path.sample(t);
+        let dir = match &self.look_at {
+            LookAt::Center => self.scene.get_bounding_center() - eye,
+            LookAt::Point(p) => *p - eye,
+            LookAt::Path(target) => target.sample(t) - eye,
+            LookAt::Ahead => self.path.tangent(t),
+        };
+        let cam = self.scene.get_mut_camera();
+        cam.eye = eye;
+        if dir.length_squared() > 0.0 {
+            cam.dir = dir.normalize();
+        }
+    }
+}
+
+impl<T: HasCycle> HasCycle for OrbitRig<T> {
+    fn cycle_frames(&self) -> Option<u32> {
+        combine_cycles(self.scene.cycle_frames(), rotation_cycle(self.speed))
+    }
+}
+
+impl<T: HasCycle> HasCycle for DollyRig<T> {
+    fn cycle_frames(&self) -> Option<u32> {
+        combine_all_cycles([
+            self.scene.cycle_frames(),
+            self.distance.cycle_frames(),
+            self.zoom.cycle_frames(),
+        ])
+    }
+}
+
+/// Open path stops at its end, so it repeats only when camera doesn't move
+impl<T: HasCycle> HasCycle for SplineRig<T> {
+    fn cycle_frames(&self) -> Option<u32> {
+        let path = if self.path.closed {
+            Some(self.frames.max(1))
+        } else if self.frames <= 1 || self.path.segments() == 0 {
+            Some(1)
+        } else {
+            None
+        };
+        combine_cycles(self.scene.cycle_frames(), path)
+    }
+}
+
+forward_scene_traits!(scene: OrbitRig, DollyRig, SplineRig);
+forward_markers!(scene: OrbitRig, DollyRig, SplineRig);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::Scene;
+
+    #[test]
+    fn orbit_keeps_radius_and_elevation() {
+        for axis in [Vec3::Y, Vec3::X, Vec3::new(1.0, 2.0, -1.0)] {
+            let rig = OrbitRig::new(Scene::default(), 50.0, 0.3)
+                .with_axis(axis)
+                .with_elevation(0.4);
+            let axis = axis.normalize();
+            for frame in 0..24 {
+                let offset = rig.eye_offset(frame);
+                assert!((offset.length() - 50.0).abs() < 1e-3);
+                let elevation = (offset.dot(axis) / offset.length()).asin();
+                assert!((elevation - 0.4).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn orbit_starts_isometric() {
+        let rig = OrbitRig::new(Scene::default(), 10.0, 0.1);
+        let offset = rig.eye_offset(0);
+        assert!(offset.x > 0.0 && offset.z > 0.0);
+        assert!((offset.x - offset.y).abs() < 1e-4);
+        assert!((offset.x - offset.z).abs() < 1e-4);
+        // Camera turns around the axis by `speed` each frame
+        let next = rig.eye_offset(1);
+        let angle =
+            Vec3::new(offset.x, 0.0, offset.z).angle_between(Vec3::new(next.x, 0.0, next.z));
+        assert!((angle - 0.1).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,99 @@
+use super::animatable::Animatable;
+use super::cycle::HasCycle;
+use super::marker::{remap_markers, HasMarkers, Marker};
+use super::timing::ping_pong_frame;
+use std::ops::Range;
+
+use crate::scene::HasScene;
+
+/// Defines how a clip is played after its last frame
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum LoopMode {
+    /// Start again from the first frame
+    #[default]
+    Loop,
+    /// Stop at the last frame
+    Once,
+    /// Play backward to the first frame and then forward again
+    PingPong,
+}
+
+impl LoopMode {
+    /// Name of the mode as it is written in metadata
+    pub fn name(&self) -> &'static str {
+        match self {
+            LoopMode::Loop => "loop",
+            LoopMode::Once => "once",
+            LoopMode::PingPong => "pingpong",
+        }
+    }
+}
+
+/// Animated scene that can be boxed, allows to keep clips of different types in one set and
+/// render them with their markers
+pub trait AnimatedScene: Animatable + HasScene + HasMarkers {}
+
+impl<T: Animatable + HasScene + HasMarkers + ?Sized> AnimatedScene for T {}
+
+/// Named animation of a unit like idle, move or die. Clip is rendered as separate sequence of
+/// `frames` frames, frames after that are mapped according to the loop mode.
+pub struct Clip<T> {
+    pub name: String,
+    pub frames: u32,
+    pub loop_mode: LoopMode,
+    pub value: T,
+}
+
+impl<T> Clip<T> {
+    pub fn new(name: &str, frames: u32, loop_mode: LoopMode, value: T) -> Self {
+        Clip {
+            name: name.to_owned(),
+            frames,
+            loop_mode,
+            value,
+        }
+    }
+
+    /// Map any frame to frame inside the clip
+    pub fn local_frame(&self, frame: u32) -> u32 {
+        if self.frames == 0 {
+            return 0;
+        }
+        let last = self.frames - 1;
+        match self.loop_mode {
+            LoopMode::Loop => frame % self.frames,
+            LoopMode::Once => frame.min(last),
+            LoopMode::PingPong => ping_pong_frame(frame, self.frames),
+        }
+    }
+}
+
+forward_scene_traits!(value: Clip);
+
+impl<T: Animatable> Animatable for Clip<T> {
+    fn evaluate(&mut self, frame: u32) {
+        let frame = self.local_frame(frame);
+        self.value.evaluate(frame);
+    }
+}
+
+impl<T: HasMarkers> HasMarkers for Clip<T> {
+    fn get_markers(&self, frames: Range<u32>) -> Vec<Marker> {
+        remap_markers(&self.value, frames, |f| self.local_frame(f))
+    }
+}
+
+/// Looping clip repeats after its frames regardless of inner animation
+impl<T: HasCycle> HasCycle for Clip<T> {
+    fn cycle_frames(&self) -> Option<u32> {
+        match self.loop_mode {
+            LoopMode::Loop => Some(self.frames.max(1)),
+            LoopMode::PingPong => Some((2 * self.frames.saturating_sub(1)).max(1)),
+            LoopMode::Once if self.frames <= 1 => Some(1),
+            LoopMode::Once => match self.value.cycle_frames() {
+                Some(1) => Some(1),
+                _ => None,
+            },
+        }
+    }
+}
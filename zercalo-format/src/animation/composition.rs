@@ -1,29 +1,140 @@
 use super::animatable::Animatable;
+use super::cycle::{combine_all_cycles, HasCycle};
+use super::marker::{HasMarkers, Marker};
+use crate::scene::{Aabb, HasBounding, HasModels, Model};
 use glam::f32::Quat;
 use glam::Vec3;
+use std::ops::Range;
 
+/// Anything that can be a part of [`Composition`]. Use `Box<dyn Part>` to mix models,
+/// switchers and nested compositions in one hierarchy.
+pub trait Part: Animatable + HasModels + HasMarkers + HasCycle {}
+
+impl<T: Animatable + HasModels + HasMarkers + HasCycle + ?Sized> Part for T {}
+
+/// Child of [`Composition`] that is rotated around its origin and then moved to `position` in
+/// the space of parent.
 pub struct RelativePart<T> {
     pub value: T,
     pub position: Vec3,
     pub rotation: Quat,
 }
 
+impl<T> RelativePart<T> {
+    pub fn new(value: T, position: Vec3, rotation: Quat) -> Self {
+        RelativePart {
+            value,
+            position,
+            rotation,
+        }
+    }
+}
+
 impl<T: Animatable> Animatable for RelativePart<T> {
-    fn animate(&mut self, frame: u32) {
-        self.value.animate(frame)
+    fn evaluate(&mut self, frame: u32) {
+        self.value.evaluate(frame)
+    }
+}
+
+impl<T: HasModels> HasModels for RelativePart<T> {
+    fn get_models(&self) -> Vec<Model> {
+        self.value
+            .get_models()
+            .iter()
+            .map(|m| m.transformed(self.position, self.rotation))
+            .collect()
     }
 }
 
+/// Hierarchy of parts with transforms relative to the parent. Compositions can be nested, so
+/// transform of each model in the result is the product of transforms along the path to it.
 pub struct Composition<T> {
     pub parts: Vec<RelativePart<T>>,
     pub position: Vec3,
     pub rotation: Quat,
 }
 
+impl<T> Default for Composition<T> {
+    fn default() -> Self {
+        Composition::new()
+    }
+}
+
+impl<T> Composition<T> {
+    /// Create empty composition at origin
+    pub fn new() -> Self {
+        Composition {
+            parts: vec![],
+            position: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+        }
+    }
+
+    /// Builder style setter of the composition transform relative to its parent
+    pub fn with_transform(mut self, position: Vec3, rotation: Quat) -> Self {
+        self.position = position;
+        self.rotation = rotation;
+        self
+    }
+
+    /// Builder style version of adding new part to `parts`
+    pub fn with_part(mut self, value: T, position: Vec3, rotation: Quat) -> Self {
+        self.parts
+            .push(RelativePart::new(value, position, rotation));
+        self
+    }
+}
+
 impl<T: Animatable> Animatable for Composition<T> {
-    fn animate(&mut self, frame: u32) {
+    fn evaluate(&mut self, frame: u32) {
         for m in self.parts.iter_mut() {
-            m.animate(frame);
+            m.evaluate(frame);
         }
     }
 }
+
+/// Models of all parts in space of the composition parent
+impl<T: HasModels> HasModels for Composition<T> {
+    fn get_models(&self) -> Vec<Model> {
+        self.parts
+            .iter()
+            .flat_map(|p| p.get_models())
+            .map(|m| m.transformed(self.position, self.rotation))
+            .collect()
+    }
+}
+
+impl<T: HasModels> HasBounding for Composition<T> {
+    fn get_bounding_volume(&self) -> Aabb {
+        self.get_models()
+            .iter()
+            .fold(Aabb::empty(), |b, m| b.union(&m.aabb()))
+    }
+}
+
+/// Markers of all parts
+impl<T: HasMarkers> HasMarkers for Composition<T> {
+    fn get_markers(&self, frames: Range<u32>) -> Vec<Marker> {
+        let mut markers: Vec<Marker> = self
+            .parts
+            .iter()
+            .flat_map(|p| p.get_markers(frames.clone()))
+            .collect();
+        markers.sort_by_key(|m| m.frame);
+        markers
+    }
+}
+
+impl<T: HasCycle> HasCycle for Composition<T> {
+    fn cycle_frames(&self) -> Option<u32> {
+        combine_all_cycles(self.parts.iter().map(|p| p.cycle_frames()))
+    }
+}
+
+impl<T: HasCycle> HasCycle for RelativePart<T> {
+    fn cycle_frames(&self) -> Option<u32> {
+        self.value.cycle_frames()
+    }
+}
+
+forward_markers!(value: RelativePart);
@@ -0,0 +1,69 @@
+use crate::scene::{Model, Scene};
+
+/// Frames that are closer than this to whole number are considered whole when rotation period is
+/// computed
+const PERIOD_TOLERANCE: f32 = 1e-2;
+
+/// Trait for animations that know after how many frames they repeat. Combinators combine cycles
+/// of inner values, so the whole animation tree gives the least length of seamless loop.
+pub trait HasCycle {
+    /// Number of frames after which animation repeats itself: frame `N` looks like frame 0. Static
+    /// values have cycle of 1 frame and `None` means the animation never repeats or the cycle is
+    /// unknown.
+    fn cycle_frames(&self) -> Option<u32>;
+}
+
+impl HasCycle for Scene {
+    fn cycle_frames(&self) -> Option<u32> {
+        Some(1)
+    }
+}
+
+impl HasCycle for Model {
+    fn cycle_frames(&self) -> Option<u32> {
+        Some(1)
+    }
+}
+
+impl<T: HasCycle + ?Sized> HasCycle for Box<T> {
+    fn cycle_frames(&self) -> Option<u32> {
+        self.as_ref().cycle_frames()
+    }
+}
+
+pub(crate) fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Least common cycle of two animations that are played together
+pub fn combine_cycles(a: Option<u32>, b: Option<u32>) -> Option<u32> {
+    let (a, b) = (a? as u64, b? as u64);
+    if a == 0 || b == 0 {
+        return Some(a.max(b).max(1) as u32);
+    }
+    u32::try_from(a / gcd(a, b) * b).ok()
+}
+
+/// Least common cycle of all given cycles, 1 for empty iterator
+pub fn combine_all_cycles<I: IntoIterator<Item = Option<u32>>>(cycles: I) -> Option<u32> {
+    cycles.into_iter().fold(Some(1), combine_cycles)
+}
+
+/// Frames of full turn when rotating by `speed` radians per frame. Turn that doesn't take whole
+/// number of frames never repeats exactly.
+pub fn rotation_cycle(speed: f32) -> Option<u32> {
+    if speed == 0.0 {
+        return Some(1);
+    }
+    let period = std::f32::consts::TAU / speed.abs();
+    let frames = period.round();
+    if frames >= 1.0 && (period - frames).abs() < PERIOD_TOLERANCE && frames <= u32::MAX as f32 {
+        Some(frames as u32)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,142 @@
+/// Curve that maps linear progress between two keyframes to eased progress. Named curves match
+/// CSS timing functions.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum Easing {
+    /// Constant speed
+    #[default]
+    Linear,
+    /// Value of the keyframe is kept until the next one
+    Step,
+    /// Starts slowly and accelerates, same as `CubicBezier(0.42, 0.0, 1.0, 1.0)`
+    EaseIn,
+    /// Starts fast and decelerates, same as `CubicBezier(0.0, 0.0, 0.58, 1.0)`
+    EaseOut,
+    /// Slow at both ends, same as `CubicBezier(0.42, 0.0, 0.58, 1.0)`
+    EaseInOut,
+    /// Cubic Bézier curve from (0, 0) to (1, 1) with control points (x1, y1) and (x2, y2).
+    /// X coordinates are clamped to range 0 .. 1, Y can overshoot to make bouncy motion.
+    CubicBezier(f32, f32, f32, f32),
+}
+
+impl Easing {
+    /// Map progress `t` in range 0 .. 1 to eased progress
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match *self {
+            Easing::Linear => t,
+            Easing::Step => {
+                if t < 1.0 {
+                    0.0
+                } else {
+                    1.0
+                }
+            }
+            Easing::EaseIn => cubic_bezier(0.42, 0.0, 1.0, 1.0, t),
+            Easing::EaseOut => cubic_bezier(0.0, 0.0, 0.58, 1.0, t),
+            Easing::EaseInOut => cubic_bezier(0.42, 0.0, 0.58, 1.0, t),
+            Easing::CubicBezier(x1, y1, x2, y2) => {
+                cubic_bezier(x1.clamp(0.0, 1.0), y1, x2.clamp(0.0, 1.0), y2, t)
+            }
+        }
+    }
+}
+
+/// Coordinate of one dimensional cubic Bézier with end points 0 and 1
+fn bezier(p1: f32, p2: f32, s: f32) -> f32 {
+    let r = 1.0 - s;
+    3.0 * r * r * s * p1 + 3.0 * r * s * s * p2 + s * s * s
+}
+
+/// Derivative of [`bezier`] by curve parameter
+fn bezier_slope(p1: f32, p2: f32, s: f32) -> f32 {
+    let r = 1.0 - s;
+    3.0 * r * r * p1 + 6.0 * r * s * (p2 - p1) + 3.0 * s * s * (1.0 - p2)
+}
+
+/// Find Y of the curve at given X. Curve parameter is found with Newton's method that falls back
+/// to bisection when the slope is too flat.
+fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, x: f32) -> f32 {
+    let mut s = x;
+    for _ in 0..8 {
+        let err = bezier(x1, x2, s) - x;
+        if err.abs() < 1e-6 {
+            return bezier(y1, y2, s);
+        }
+        let slope = bezier_slope(x1, x2, s);
+        if slope.abs() < 1e-6 {
+            break;
+        }
+        s = (s - err / slope).clamp(0.0, 1.0);
+    }
+
+    let (mut lo, mut hi) = (0.0, 1.0);
+    s = x;
+    for _ in 0..32 {
+        let v = bezier(x1, x2, s);
+        if (v - x).abs() < 1e-6 {
+            break;
+        }
+        if v < x {
+            lo = s;
+        } else {
+            hi = s;
+        }
+        s = (lo + hi) * 0.5;
+    }
+    bezier(y1, y2, s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURVES: [Easing; 7] = [
+        Easing::Linear,
+        Easing::Step,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+        Easing::CubicBezier(0.3, -0.5, 0.7, 1.5),
+        Easing::CubicBezier(0.0, 0.0, 0.0, 0.0),
+    ];
+
+    #[test]
+    fn end_points() {
+        for easing in CURVES {
+            assert!(easing.apply(0.0).abs() < 1e-5, "{:?}", easing);
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-5, "{:?}", easing);
+            // Progress out of range is clamped
+            assert_eq!(easing.apply(-1.0), easing.apply(0.0));
+            assert_eq!(easing.apply(2.0), easing.apply(1.0));
+        }
+    }
+
+    #[test]
+    fn step_and_linear() {
+        assert_eq!(Easing::Step.apply(0.5), 0.0);
+        assert_eq!(Easing::Step.apply(0.999), 0.0);
+        assert_eq!(Easing::Linear.apply(0.25), 0.25);
+        assert_eq!(Easing::default(), Easing::Linear);
+    }
+
+    #[test]
+    fn named_curves() {
+        assert!(Easing::EaseIn.apply(0.5) < 0.5);
+        assert!(Easing::EaseOut.apply(0.5) > 0.5);
+        assert!((Easing::EaseInOut.apply(0.5) - 0.5).abs() < 1e-4);
+        // Linear Bézier is the identity
+        let linear = Easing::CubicBezier(0.25, 0.25, 0.75, 0.75);
+        for t in [0.1, 0.3, 0.6, 0.9] {
+            assert!((linear.apply(t) - t).abs() < 1e-4);
+        }
+        // Monotonic curve stays monotonic
+        let mut last = 0.0;
+        for i in 1..=20 {
+            let v = Easing::EaseInOut.apply(i as f32 / 20.0);
+            assert!(v >= last);
+            last = v;
+        }
+        // Control points outside of 0 .. 1 overshoot
+        assert!(CURVES[5].apply(0.9) > 1.0);
+    }
+}
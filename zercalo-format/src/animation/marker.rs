@@ -0,0 +1,128 @@
+use super::animatable::Animatable;
+use super::cycle::HasCycle;
+use crate::scene::{Model, Scene};
+use std::ops::Range;
+
+/// Named event at the frame of animation like a step sound or a shot. Game code reads markers
+/// from sprite metadata to trigger sounds and effects in sync with baked frames.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Marker {
+    pub name: String,
+    pub frame: u32,
+    /// Optional data for the game, like bone or effect name
+    pub payload: Option<String>,
+}
+
+impl Marker {
+    pub fn new(name: &str, frame: u32) -> Self {
+        Marker {
+            name: name.to_owned(),
+            frame,
+            payload: None,
+        }
+    }
+
+    pub fn with_payload(mut self, payload: &str) -> Self {
+        self.payload = Some(payload.to_owned());
+        self
+    }
+
+    /// Same marker moved to another frame
+    pub fn at(&self, frame: u32) -> Self {
+        Marker {
+            frame,
+            ..self.clone()
+        }
+    }
+}
+
+/// Trait for animations that can have markers. Combinators translate markers of inner values to
+/// own frames, so markers stay at the frames where their event is shown.
+pub trait HasMarkers {
+    /// Get markers that fire at the frames of the range, sorted by frame
+    fn get_markers(&self, frames: Range<u32>) -> Vec<Marker>;
+}
+
+impl HasMarkers for Scene {
+    fn get_markers(&self, _frames: Range<u32>) -> Vec<Marker> {
+        vec![]
+    }
+}
+
+impl HasMarkers for Model {
+    fn get_markers(&self, _frames: Range<u32>) -> Vec<Marker> {
+        vec![]
+    }
+}
+
+impl<T: HasMarkers + ?Sized> HasMarkers for Box<T> {
+    fn get_markers(&self, frames: Range<u32>) -> Vec<Marker> {
+        self.as_ref().get_markers(frames)
+    }
+}
+
+/// Collect markers of the inner value for combinators that show frame `map(frame)` of inner value
+/// at `frame`. Markers fire when the shown frame changes, so held frames don't repeat them.
+pub fn remap_markers<T, F>(inner: &T, frames: Range<u32>, map: F) -> Vec<Marker>
+where
+    T: HasMarkers + ?Sized,
+    F: Fn(u32) -> u32,
+{
+    let mut markers = vec![];
+    for frame in frames {
+        let shown = map(frame);
+        if frame > 0 && map(frame - 1) == shown {
+            continue;
+        }
+        for m in inner.get_markers(shown..shown + 1) {
+            markers.push(m.at(frame));
+        }
+    }
+    markers
+}
+
+/// Adds markers to the inner animation, they are given in frames of the inner animation
+pub struct Marked<T> {
+    pub value: T,
+    pub markers: Vec<Marker>,
+}
+
+impl<T> Marked<T> {
+    pub fn new(value: T, markers: Vec<Marker>) -> Self {
+        Marked { value, markers }
+    }
+
+    /// Builder style way to add single marker
+    pub fn with_marker(mut self, marker: Marker) -> Self {
+        self.markers.push(marker);
+        self
+    }
+}
+
+impl<T: HasMarkers> HasMarkers for Marked<T> {
+    fn get_markers(&self, frames: Range<u32>) -> Vec<Marker> {
+        let mut markers = self.value.get_markers(frames.clone());
+        markers.extend(
+            self.markers
+                .iter()
+                .filter(|m| frames.contains(&m.frame))
+                .cloned(),
+        );
+        markers.sort_by_key(|m| m.frame);
+        markers
+    }
+}
+
+impl<T: Animatable> Animatable for Marked<T> {
+    fn evaluate(&mut self, frame: u32) {
+        self.value.evaluate(frame);
+    }
+}
+
+forward_scene_traits!(value: Marked);
+
+impl<T: HasCycle> HasCycle for Marked<T> {
+    fn cycle_frames(&self) -> Option<u32> {
+        self.value.cycle_frames()
+    }
+}
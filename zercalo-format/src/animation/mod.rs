@@ -1,11 +1,92 @@
+/// Implements scene access traits for combinators that only wrap inner value stored in the given
+/// field, so everything except the frame is taken from the inner value
+macro_rules! forward_scene_traits {
+    ($field:ident: $($combinator:ident),*) => {
+        $(
+            impl<T: $crate::scene::HasCamera> $crate::scene::HasCamera for $combinator<T> {
+                fn get_camera(&self) -> &$crate::scene::Camera {
+                    self.$field.get_camera()
+                }
+            }
+
+            impl<T: $crate::scene::HasMutCamera> $crate::scene::HasMutCamera for $combinator<T> {
+                fn get_mut_camera(&mut self) -> &mut $crate::scene::Camera {
+                    self.$field.get_mut_camera()
+                }
+            }
+
+            impl<T: $crate::scene::HasBounding> $crate::scene::HasBounding for $combinator<T> {
+                fn get_bounding_volume(&self) -> $crate::scene::Aabb {
+                    self.$field.get_bounding_volume()
+                }
+            }
+
+            impl<T: $crate::scene::HasModels> $crate::scene::HasModels for $combinator<T> {
+                fn get_models(&self) -> Vec<$crate::scene::Model> {
+                    self.$field.get_models()
+                }
+            }
+
+            impl<T: $crate::scene::HasScene> $crate::scene::HasScene for $combinator<T> {
+                fn get_scene(&self) -> &$crate::scene::Scene {
+                    self.$field.get_scene()
+                }
+            }
+
+            impl<T: $crate::scene::HasMutScene> $crate::scene::HasMutScene for $combinator<T> {
+                fn get_scene_mut(&mut self) -> &mut $crate::scene::Scene {
+                    self.$field.get_scene_mut()
+                }
+            }
+        )*
+    };
+}
+
+/// Implements [`marker::HasMarkers`] for combinators that don't change timing of inner value
+/// stored in the given field
+macro_rules! forward_markers {
+    ($field:ident: $($combinator:ident),*) => {
+        $(
+            impl<T: $crate::animation::marker::HasMarkers> $crate::animation::marker::HasMarkers
+                for $combinator<T>
+            {
+                fn get_markers(
+                    &self,
+                    frames: std::ops::Range<u32>,
+                ) -> Vec<$crate::animation::marker::Marker> {
+                    self.$field.get_markers(frames)
+                }
+            }
+        )*
+    };
+}
+
 pub mod animatable;
+pub mod camera;
+pub mod clip;
 pub mod composition;
+pub mod cycle;
+pub mod easing;
+pub mod marker;
 pub mod rotation;
+pub mod sequence;
+pub mod spline;
 pub mod stepper;
 pub mod switcher;
+pub mod timing;
+pub mod track;
 
 pub use animatable::Animatable;
-pub use composition::Composition;
+pub use camera::{DollyRig, LookAt, OrbitRig, SplineRig};
+pub use clip::{AnimatedScene, Clip, LoopMode};
+pub use composition::{Composition, Part, RelativePart};
+pub use cycle::HasCycle;
+pub use easing::Easing;
+pub use marker::{HasMarkers, Marked, Marker};
 pub use rotation::RotationView;
+pub use sequence::{Parallel, Sequence};
+pub use spline::CatmullRom;
 pub use stepper::Stepper;
 pub use switcher::Switcher;
+pub use timing::{Hold, Offset, PingPong, Reverse, Speed};
+pub use track::{Interpolate, Keyframe, SceneTrack, Track, Tracked};
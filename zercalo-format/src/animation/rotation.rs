@@ -1,56 +1,56 @@
 use super::animatable::Animatable;
-use crate::scene::{Camera, HasBounding, HasCamera, HasMutCamera, HasMutScene, HasScene, Scene};
+use super::cycle::{combine_cycles, rotation_cycle, HasCycle};
+use crate::scene::{HasBounding, HasCamera, HasMutCamera};
 use glam::f32::Quat;
 use glam::Vec3;
 
+/// Combinator that rotates camera around the scene center by `rotation_speed` radians each frame
 pub struct RotationView<T> {
     pub scene: T,
     pub target_y: Option<f32>,
     pub rotation_speed: f32,
+    /// Camera position at frame 0
+    pub origin: Vec3,
 }
 
-impl<T: HasCamera> HasCamera for RotationView<T> {
-    fn get_camera(&self) -> &Camera {
-        self.scene.get_camera()
-    }
-}
-
-impl<T: HasMutCamera> HasMutCamera for RotationView<T> {
-    fn get_mut_camera(&mut self) -> &mut Camera {
-        self.scene.get_mut_camera()
-    }
-}
-
-impl<T: HasBounding> HasBounding for RotationView<T> {
-    fn get_bounding_volume(&self) -> (Vec3, Vec3) {
-        self.scene.get_bounding_volume()
-    }
-}
-
-impl<T: HasScene> HasScene for RotationView<T> {
-    fn get_scene(&self) -> &Scene {
-        self.scene.get_scene()
+impl<T: HasCamera> RotationView<T> {
+    /// Wrap the scene, current camera position is taken as position at frame 0
+    pub fn new(scene: T, target_y: Option<f32>, rotation_speed: f32) -> Self {
+        let origin = scene.get_camera().eye;
+        RotationView {
+            scene,
+            target_y,
+            rotation_speed,
+            origin,
+        }
     }
 }
 
-impl<T: HasMutScene> HasMutScene for RotationView<T> {
-    fn get_scene_mut(&mut self) -> &mut Scene {
-        self.scene.get_scene_mut()
-    }
-}
+forward_scene_traits!(scene: RotationView);
 
 impl<T: Animatable + HasMutCamera + HasBounding> Animatable for RotationView<T> {
-    fn animate(&mut self, frame: u32) {
-        self.scene.animate(frame);
+    fn evaluate(&mut self, frame: u32) {
+        self.scene.evaluate(frame);
 
-        let quat = Quat::from_axis_angle(Vec3::Y, self.rotation_speed);
+        let quat = Quat::from_axis_angle(Vec3::Y, self.rotation_speed * frame as f32);
 
         let mut target = self.scene.get_bounding_center();
         if let Some(y) = self.target_y {
             target.y = y;
         }
         let cam = self.scene.get_mut_camera();
-        cam.eye = target + quat.mul_vec3(cam.eye - target);
+        cam.eye = target + quat.mul_vec3(self.origin - target);
         cam.dir = (target - cam.eye).normalize();
     }
 }
+
+forward_markers!(scene: RotationView);
+
+impl<T: HasCycle> HasCycle for RotationView<T> {
+    fn cycle_frames(&self) -> Option<u32> {
+        combine_cycles(
+            self.scene.cycle_frames(),
+            rotation_cycle(self.rotation_speed),
+        )
+    }
+}
@@ -0,0 +1,301 @@
+use super::animatable::Animatable;
+use super::cycle::{combine_cycles, HasCycle};
+use super::marker::{HasMarkers, Marker};
+use crate::scene::{
+    Aabb, Camera, HasBounding, HasCamera, HasModels, HasMutCamera, HasMutScene, HasScene, Model,
+    Scene,
+};
+use std::ops::Range;
+
+/// Plays `first` for `first_frames` frames and then `second`. Second animation is evaluated with
+/// frames relative to its start, so it begins from its own frame 0.
+pub struct Sequence<A, B> {
+    pub first: A,
+    pub first_frames: u32,
+    pub second: B,
+    /// Whether `first` is active at the last evaluated frame
+    pub first_active: bool,
+}
+
+impl<A, B> Sequence<A, B> {
+    pub fn new(first: A, first_frames: u32, second: B) -> Self {
+        Sequence {
+            first,
+            first_frames,
+            second,
+            first_active: true,
+        }
+    }
+}
+
+impl<A: HasCamera, B: HasCamera> HasCamera for Sequence<A, B> {
+    fn get_camera(&self) -> &Camera {
+        if self.first_active {
+            self.first.get_camera()
+        } else {
+            self.second.get_camera()
+        }
+    }
+}
+
+impl<A: HasMutCamera, B: HasMutCamera> HasMutCamera for Sequence<A, B> {
+    fn get_mut_camera(&mut self) -> &mut Camera {
+        if self.first_active {
+            self.first.get_mut_camera()
+        } else {
+            self.second.get_mut_camera()
+        }
+    }
+}
+
+impl<A: HasBounding, B: HasBounding> HasBounding for Sequence<A, B> {
+    fn get_bounding_volume(&self) -> Aabb {
+        if self.first_active {
+            self.first.get_bounding_volume()
+        } else {
+            self.second.get_bounding_volume()
+        }
+    }
+}
+
+impl<A: HasModels, B: HasModels> HasModels for Sequence<A, B> {
+    fn get_models(&self) -> Vec<Model> {
+        if self.first_active {
+            self.first.get_models()
+        } else {
+            self.second.get_models()
+        }
+    }
+}
+
+impl<A: HasScene, B: HasScene> HasScene for Sequence<A, B> {
+    fn get_scene(&self) -> &Scene {
+        if self.first_active {
+            self.first.get_scene()
+        } else {
+            self.second.get_scene()
+        }
+    }
+}
+
+impl<A: HasMutScene, B: HasMutScene> HasMutScene for Sequence<A, B> {
+    fn get_scene_mut(&mut self) -> &mut Scene {
+        if self.first_active {
+            self.first.get_scene_mut()
+        } else {
+            self.second.get_scene_mut()
+        }
+    }
+}
+
+impl<A: Animatable, B: Animatable> Animatable for Sequence<A, B> {
+    fn evaluate(&mut self, frame: u32) {
+        self.first_active = frame < self.first_frames;
+        if self.first_active {
+            self.first.evaluate(frame);
+        } else {
+            self.second.evaluate(frame - self.first_frames);
+        }
+    }
+}
+
+impl<A: HasMarkers, B: HasMarkers> HasMarkers for Sequence<A, B> {
+    fn get_markers(&self, frames: Range<u32>) -> Vec<Marker> {
+        let split = frames.end.min(self.first_frames).max(frames.start);
+        let mut markers = self.first.get_markers(frames.start..split);
+        let second_start = split.max(self.first_frames) - self.first_frames;
+        let second_end = frames.end.max(self.first_frames) - self.first_frames;
+        markers.extend(
+            self.second
+                .get_markers(second_start..second_end)
+                .into_iter()
+                .map(|m| m.at(m.frame + self.first_frames)),
+        );
+        markers
+    }
+}
+
+/// Second animation stays after the first one, so sequence repeats only when both are static
+impl<A: HasCycle, B: HasCycle> HasCycle for Sequence<A, B> {
+    fn cycle_frames(&self) -> Option<u32> {
+        match (self.first.cycle_frames(), self.second.cycle_frames()) {
+            (Some(1), Some(1)) => Some(1),
+            _ => None,
+        }
+    }
+}
+
+/// Evaluates both animations at the same frame. Models of both are collected together, while
+/// lights and camera are taken from `main`, so `side` can be anything that adds models like
+/// particles or a [`super::Composition`]. Scene of `main` with models of `side` is merged on each
+/// evaluation, so renderer sees both.
+pub struct Parallel<A, B> {
+    pub main: A,
+    pub side: B,
+    /// Merged scene at the last evaluated frame
+    pub scene: Scene,
+}
+
+impl<A, B> Parallel<A, B> {
+    pub fn new(main: A, side: B) -> Self {
+        Parallel {
+            main,
+            side,
+            scene: Scene::default(),
+        }
+    }
+}
+
+impl<A, B> HasCamera for Parallel<A, B> {
+    fn get_camera(&self) -> &Camera {
+        &self.scene.camera
+    }
+}
+
+impl<A, B> HasMutCamera for Parallel<A, B> {
+    fn get_mut_camera(&mut self) -> &mut Camera {
+        &mut self.scene.camera
+    }
+}
+
+impl<A: HasBounding, B: HasBounding> HasBounding for Parallel<A, B> {
+    fn get_bounding_volume(&self) -> Aabb {
+        self.main
+            .get_bounding_volume()
+            .union(&self.side.get_bounding_volume())
+    }
+}
+
+impl<A: HasModels, B: HasModels> HasModels for Parallel<A, B> {
+    fn get_models(&self) -> Vec<Model> {
+        let mut models = self.main.get_models();
+        models.extend(self.side.get_models());
+        models
+    }
+}
+
+impl<A, B> HasScene for Parallel<A, B> {
+    fn get_scene(&self) -> &Scene {
+        &self.scene
+    }
+}
+
+impl<A, B> HasMutScene for Parallel<A, B> {
+    fn get_scene_mut(&mut self) -> &mut Scene {
+        &mut self.scene
+    }
+}
+
+impl<A: Animatable + HasScene, B: Animatable + HasModels> Animatable for Parallel<A, B> {
+    fn evaluate(&mut self, frame: u32) {
+        self.main.evaluate(frame);
+        self.side.evaluate(frame);
+        self.scene = self.main.get_scene().clone();
+        self.scene.models.extend(self.side.get_models());
+    }
+}
+
+impl<A: HasMarkers, B: HasMarkers> HasMarkers for Parallel<A, B> {
+    fn get_markers(&self, frames: Range<u32>) -> Vec<Marker> {
+        let mut markers = self.main.get_markers(frames.clone());
+        markers.extend(self.side.get_markers(frames));
+        markers.sort_by_key(|m| m.frame);
+        markers
+    }
+}
+
+impl<A: HasCycle, B: HasCycle> HasCycle for Parallel<A, B> {
+    fn cycle_frames(&self) -> Option<u32> {
+        combine_cycles(self.main.cycle_frames(), self.side.cycle_frames())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::Marked;
+    use crate::color::ColorRGBA;
+    use glam::UVec3;
+
+    /// Remembers the last evaluated frame
+    #[derive(Default)]
+    struct Shown(Option<u32>);
+
+    impl Animatable for Shown {
+        fn evaluate(&mut self, frame: u32) {
+            self.0 = Some(frame);
+        }
+    }
+
+    impl HasMarkers for Shown {
+        fn get_markers(&self, _frames: Range<u32>) -> Vec<Marker> {
+            vec![]
+        }
+    }
+
+    fn marked(markers: &[(&str, u32)]) -> Marked<Shown> {
+        let markers = markers.iter().map(|(n, f)| Marker::new(n, *f)).collect();
+        Marked::new(Shown::default(), markers)
+    }
+
+    fn names(markers: Vec<Marker>) -> Vec<(String, u32)> {
+        markers.into_iter().map(|m| (m.name, m.frame)).collect()
+    }
+
+    fn at(name: &str, frame: u32) -> (String, u32) {
+        (name.to_owned(), frame)
+    }
+
+    #[test]
+    fn sequence_maps_frames() {
+        let mut sequence = Sequence::new(Shown::default(), 3, Shown::default());
+        for frame in 0..3 {
+            sequence.evaluate(frame);
+            assert!(sequence.first_active);
+            assert_eq!(sequence.first.0, Some(frame));
+            assert_eq!(sequence.second.0, None);
+        }
+        for frame in 3..6 {
+            sequence.evaluate(frame);
+            assert!(!sequence.first_active);
+            assert_eq!(sequence.first.0, Some(2));
+            assert_eq!(sequence.second.0, Some(frame - 3));
+        }
+        sequence.evaluate(1);
+        assert!(sequence.first_active);
+        assert_eq!(sequence.first.0, Some(1));
+    }
+
+    #[test]
+    fn sequence_markers() {
+        let sequence = Sequence::new(
+            marked(&[("a", 1), ("late", 3)]),
+            3,
+            marked(&[("b", 0), ("c", 2)]),
+        );
+        // Markers of the first animation after its end are not shown
+        let all = names(sequence.get_markers(0..10));
+        assert_eq!(all, vec![at("a", 1), at("b", 3), at("c", 5)]);
+        assert_eq!(names(sequence.get_markers(2..4)), vec![at("b", 3)]);
+        assert_eq!(names(sequence.get_markers(4..6)), vec![at("c", 5)]);
+        assert_eq!(names(sequence.get_markers(0..2)), vec![at("a", 1)]);
+    }
+
+    #[test]
+    fn parallel_scene_has_side_models() {
+        let solid = Model::from_function(UVec3::ONE, |_| ColorRGBA::white());
+        let main = Scene {
+            models: vec![solid.clone()],
+            ..Scene::default()
+        };
+        let side = Scene {
+            models: vec![solid.clone(), solid],
+            ..Scene::default()
+        };
+        let mut parallel = Parallel::new(main, side);
+        parallel.evaluate(0);
+        assert_eq!(parallel.get_scene().models.len(), 3);
+        parallel.evaluate(1);
+        assert_eq!(parallel.get_scene().models.len(), 3);
+    }
+}
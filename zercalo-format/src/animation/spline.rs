@@ -0,0 +1,144 @@
+use glam::Vec3;
+
+/// Uniform Catmull-Rom spline that passes through all its points. Open spline starts at the
+/// first point and ends at the last one, closed spline also connects the last point with the
+/// first.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct CatmullRom {
+    pub points: Vec<Vec3>,
+    pub closed: bool,
+}
+
+impl CatmullRom {
+    pub fn new(points: Vec<Vec3>, closed: bool) -> Self {
+        CatmullRom { points, closed }
+    }
+
+    /// Amount of curve segments between points
+    pub fn segments(&self) -> usize {
+        match self.points.len() {
+            0 | 1 => 0,
+            n if self.closed => n,
+            n => n - 1,
+        }
+    }
+
+    /// Control point with index that can be out of range, it is wrapped for closed spline and
+    /// clamped for open one
+    fn point(&self, i: isize) -> Vec3 {
+        let n = self.points.len() as isize;
+        let i = if self.closed {
+            i.rem_euclid(n)
+        } else {
+            i.clamp(0, n - 1)
+        };
+        self.points[i as usize]
+    }
+
+    /// Find segment and position inside it for parameter `t` in range 0 .. 1
+    fn locate(&self, t: f32) -> (isize, f32) {
+        let segments = self.segments() as f32;
+        let t = if self.closed {
+            t.rem_euclid(1.0)
+        } else {
+            t.clamp(0.0, 1.0)
+        };
+        let s = t * segments;
+        let i = s.floor().min(segments - 1.0);
+        (i as isize, s - i)
+    }
+
+    /// Get point on the curve, `t` is in range 0 .. 1 along the whole spline. Spline without
+    /// points is at origin.
+    pub fn sample(&self, t: f32) -> Vec3 {
+        if self.segments() == 0 {
+            return self.points.first().copied().unwrap_or(Vec3::ZERO);
+        }
+        let (i, u) = self.locate(t);
+        let (p0, p1, p2, p3) = (
+            self.point(i - 1),
+            self.point(i),
+            self.point(i + 1),
+            self.point(i + 2),
+        );
+        let (u2, u3) = (u * u, u * u * u);
+        0.5 * (2.0 * p1
+            + (p2 - p0) * u
+            + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * u2
+            + (3.0 * p1 - p0 - 3.0 * p2 + p3) * u3)
+    }
+
+    /// Get direction of the curve at `t`, not normalized
+    pub fn tangent(&self, t: f32) -> Vec3 {
+        if self.segments() == 0 {
+            return Vec3::ZERO;
+        }
+        let (i, u) = self.locate(t);
+        let (p0, p1, p2, p3) = (
+            self.point(i - 1),
+            self.point(i),
+            self.point(i + 1),
+            self.point(i + 2),
+        );
+        0.5 * ((p2 - p0)
+            + 2.0 * (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * u
+            + 3.0 * (3.0 * p1 - p0 - 3.0 * p2 + p3) * u * u)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points() -> Vec<Vec3> {
+        vec![
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(4.0, 2.0, 0.0),
+            Vec3::new(8.0, 0.0, 4.0),
+            Vec3::new(4.0, -2.0, 8.0),
+        ]
+    }
+
+    fn close(a: Vec3, b: Vec3) -> bool {
+        (a - b).length() < 1e-4
+    }
+
+    #[test]
+    fn open_spline_hits_points() {
+        let spline = CatmullRom::new(points(), false);
+        assert_eq!(spline.segments(), 3);
+        assert!(close(spline.sample(0.0), points()[0]));
+        assert!(close(spline.sample(1.0), points()[3]));
+        assert!(close(spline.sample(1.0 / 3.0), points()[1]));
+        assert!(close(spline.sample(2.0 / 3.0), points()[2]));
+        // Parameter out of range is clamped
+        assert!(close(spline.sample(-0.5), points()[0]));
+        assert!(close(spline.sample(1.5), points()[3]));
+    }
+
+    #[test]
+    fn closed_spline_wraps() {
+        let spline = CatmullRom::new(points(), true);
+        assert_eq!(spline.segments(), 4);
+        assert!(close(spline.sample(0.0), points()[0]));
+        assert!(close(spline.sample(0.75), points()[3]));
+        assert!(close(spline.sample(1.0), points()[0]));
+        assert!(close(spline.sample(1.3), spline.sample(0.3)));
+        assert!(close(spline.sample(-0.2), spline.sample(0.8)));
+        // Curve goes smoothly through the first point
+        assert!(close(spline.tangent(0.0), spline.tangent(1.0)));
+        assert!(close(
+            spline.tangent(0.0),
+            0.5 * (points()[1] - points()[3])
+        ));
+    }
+
+    #[test]
+    fn degenerate_splines() {
+        assert_eq!(CatmullRom::default().sample(0.5), Vec3::ZERO);
+        let single = CatmullRom::new(vec![Vec3::ONE], true);
+        assert_eq!(single.segments(), 0);
+        assert_eq!(single.sample(0.5), Vec3::ONE);
+        assert_eq!(single.tangent(0.5), Vec3::ZERO);
+    }
+}
@@ -1,8 +1,8 @@
 use super::animatable::Animatable;
-use crate::scene::{Camera, HasBounding, HasCamera, HasMutCamera, HasMutScene, HasScene, Scene};
-use glam::Vec3;
+use super::cycle::HasCycle;
 
-/// Allows update given value each frame by saved closure
+/// Allows update given value each frame by saved closure. The closure is called after the value
+/// is evaluated and should set state only from the frame number, not accumulate it.
 pub struct Stepper<T> {
     pub value: T,
     pub stepper: Box<dyn FnMut(&mut T, u32)>,
@@ -19,39 +19,21 @@ impl<T> Stepper<T> {
         }
     }
 }
-impl<T: HasCamera> HasCamera for Stepper<T> {
-    fn get_camera(&self) -> &Camera {
-        self.value.get_camera()
-    }
-}
 
-impl<T: HasMutCamera> HasMutCamera for Stepper<T> {
-    fn get_mut_camera(&mut self) -> &mut Camera {
-        self.value.get_mut_camera()
-    }
-}
-
-impl<T: HasBounding> HasBounding for Stepper<T> {
-    fn get_bounding_volume(&self) -> (Vec3, Vec3) {
-        self.value.get_bounding_volume()
-    }
-}
+forward_scene_traits!(value: Stepper);
 
-impl<T: HasScene> HasScene for Stepper<T> {
-    fn get_scene(&self) -> &Scene {
-        self.value.get_scene()
+impl<T: Animatable> Animatable for Stepper<T> {
+    fn evaluate(&mut self, frame: u32) {
+        self.value.evaluate(frame);
+        (self.stepper)(&mut self.value, frame);
     }
 }
 
-impl<T: HasMutScene> HasMutScene for Stepper<T> {
-    fn get_scene_mut(&mut self) -> &mut Scene {
-        self.value.get_scene_mut()
-    }
-}
+forward_markers!(value: Stepper);
 
-impl<T: Animatable> Animatable for Stepper<T> {
-    fn animate(&mut self, frame: u32) {
-        self.value.animate(frame);
-        (self.stepper)(&mut self.value, frame);
+/// Closure of stepper can do anything, so its cycle is unknown
+impl<T> HasCycle for Stepper<T> {
+    fn cycle_frames(&self) -> Option<u32> {
+        None
     }
 }
@@ -1,10 +1,15 @@
 use super::animatable::Animatable;
-use crate::scene::{Camera, HasBounding, HasCamera, HasMutCamera, HasMutScene, HasScene, Scene};
-use glam::Vec3;
-use log::*;
+use super::cycle::HasCycle;
+use super::marker::{HasMarkers, Marker};
+use crate::scene::{
+    Aabb, Camera, HasBounding, HasCamera, HasModels, HasMutCamera, HasMutScene, HasScene, Model,
+    Scene,
+};
+use std::ops::Range;
 
 /// Combinator that allows you to switch between models on time. First N frames first variant, next T
-/// frames other and etc. Can be used to animate complex models.
+/// frames other and etc. Can be used to animate complex models. Active variant is evaluated with
+/// the frame relative to its start, so nested switchers restart with each activation.
 #[derive(Debug, Clone)]
 pub struct Switcher<T> {
     /// Stores frames
@@ -13,12 +18,8 @@ pub struct Switcher<T> {
     pub active: u32,
     /// Contains end frame for each corresponding variant
     pub schedule: Vec<u32>,
-    /// Last known frame
-    pub last_frame: u32,
     /// Loop animation if reached the end?
     pub looping: bool,
-    /// Saved offset for looping logic
-    pub loop_offset: u32,
 }
 
 impl<T> Switcher<T> {
@@ -36,9 +37,7 @@ impl<T> Switcher<T> {
             variants,
             active: 0,
             schedule,
-            last_frame: 0,
             looping: true,
-            loop_offset: 0,
         }
     }
 
@@ -52,6 +51,23 @@ impl<T> Switcher<T> {
         &mut self.variants[self.active as usize]
     }
 
+    /// Get index of variant that is active at the frame and the frame relative to the start of
+    /// that variant
+    pub fn variant_at(&self, frame: u32) -> (usize, u32) {
+        let cycle = self.cycle_len();
+        let frame = if self.looping && cycle > 0 {
+            frame % cycle
+        } else {
+            frame
+        };
+        let i = self
+            .schedule
+            .partition_point(|end| *end <= frame)
+            .min(self.variants.len() - 1);
+        let start = if i == 0 { 0 } else { self.schedule[i - 1] };
+        (i, frame - start)
+    }
+
     /// Get number of frames that takes to make single cycle
     pub fn cycle_len(&self) -> u32 {
         if let Some(i) = self.schedule.last() {
@@ -75,11 +91,17 @@ impl<T: HasMutCamera> HasMutCamera for Switcher<T> {
 }
 
 impl<T: HasBounding> HasBounding for Switcher<T> {
-    fn get_bounding_volume(&self) -> (Vec3, Vec3) {
+    fn get_bounding_volume(&self) -> Aabb {
         self.current().get_bounding_volume()
     }
 }
 
+impl<T: HasModels> HasModels for Switcher<T> {
+    fn get_models(&self) -> Vec<Model> {
+        self.current().get_models()
+    }
+}
+
 impl<T: HasScene> HasScene for Switcher<T> {
     fn get_scene(&self) -> &Scene {
         self.current().get_scene()
@@ -93,43 +115,41 @@ impl<T: HasMutScene> HasMutScene for Switcher<T> {
 }
 
 impl<T: Animatable> Animatable for Switcher<T> {
-    fn animate(&mut self, frame: u32) {
-        if frame < self.last_frame {
-            warn!(
-                "Switcher<{}> animate frame backward, last frame was {}, but got new {}",
-                std::any::type_name::<T>(),
-                self.last_frame,
-                frame
-            );
-            return;
-        };
-        assert!(
-            (self.active as usize) < self.variants.len(),
-            "Active frame index {} is greater than frames array {}",
-            self.active,
-            self.variants.len()
-        );
-        assert!(
-            (self.active as usize) < self.schedule.len(),
-            "Active frame index {} is greater than schedule array {}",
-            self.active,
-            self.schedule.len()
-        );
+    fn evaluate(&mut self, frame: u32) {
+        let (active, local_frame) = self.variant_at(frame);
+        self.active = active as u32;
+        self.current_mut().evaluate(local_frame);
+    }
+}
 
-        let border = self.schedule[self.active as usize] + self.loop_offset;
-        if frame >= border {
-            if self.active as usize >= self.variants.len() - 1 {
-                if self.looping {
-                    self.active = 0;
-                    self.loop_offset = frame;
-                } else {
-                    self.active = (self.variants.len() - 1) as u32;
-                }
-            } else {
-                self.active += 1;
+/// Markers of active variant fire when its local frame changes, so each activation of a variant
+/// repeats its markers
+impl<T: HasMarkers> HasMarkers for Switcher<T> {
+    fn get_markers(&self, frames: Range<u32>) -> Vec<Marker> {
+        let mut markers = vec![];
+        for frame in frames {
+            let (active, local) = self.variant_at(frame);
+            if frame > 0 && self.variant_at(frame - 1) == (active, local) {
+                continue;
             }
+            for m in self.variants[active].get_markers(local..local + 1) {
+                markers.push(m.at(frame));
+            }
+        }
+        markers
+    }
+}
+
+/// Looping switcher restarts variants on each activation, so it repeats after its schedule
+/// regardless of variants. Switcher that doesn't loop holds the last variant.
+impl<T: HasCycle> HasCycle for Switcher<T> {
+    fn cycle_frames(&self) -> Option<u32> {
+        if self.looping {
+            Some(self.cycle_len().max(1))
+        } else if self.variants.len() == 1 {
+            self.variants[0].cycle_frames()
+        } else {
+            None
         }
-        self.last_frame = frame;
-        self.current_mut().animate(frame);
     }
 }
@@ -0,0 +1,387 @@
+use super::animatable::Animatable;
+use super::cycle::{gcd, HasCycle};
+use super::marker::{remap_markers, HasMarkers, Marker};
+use std::ops::Range;
+
+/// Largest denominator of speed factor when it is matched to a fraction to compute cycle
+const SPEED_MAX_DENOMINATOR: u64 = 1000;
+/// Speed factor that differs from a fraction less than this is considered equal to it
+const SPEED_TOLERANCE: f64 = 1e-6;
+/// How many frames are compared to check that cycle of speed factor holds with rounding errors
+const SPEED_CHECK_FRAMES: u32 = 4096;
+
+/// Map frame to the range `0 .. frames` going forward and then backward, so the first and the
+/// last frames are not repeated
+pub fn ping_pong_frame(frame: u32, frames: u32) -> u32 {
+    let last = frames.saturating_sub(1);
+    if last == 0 {
+        return 0;
+    }
+    let i = frame % (2 * last);
+    if i > last {
+        2 * last - i
+    } else {
+        i
+    }
+}
+
+/// Changes playback speed, `factor` 2.0 plays twice as fast and 0.5 shows each frame twice
+pub struct Speed<T> {
+    pub value: T,
+    pub factor: f32,
+}
+
+impl<T> Speed<T> {
+    pub fn new(value: T, factor: f32) -> Self {
+        Speed { value, factor }
+    }
+}
+
+/// Plays first `frames` frames backward, frames after that show the first frame
+pub struct Reverse<T> {
+    pub value: T,
+    pub frames: u32,
+}
+
+impl<T> Reverse<T> {
+    pub fn new(value: T, frames: u32) -> Self {
+        Reverse { value, frames }
+    }
+}
+
+/// Loops first `frames` frames forward and backward
+pub struct PingPong<T> {
+    pub value: T,
+    pub frames: u32,
+}
+
+impl<T> PingPong<T> {
+    pub fn new(value: T, frames: u32) -> Self {
+        PingPong { value, frames }
+    }
+}
+
+/// Plays first `frames` frames and then holds the last one
+pub struct Hold<T> {
+    pub value: T,
+    pub frames: u32,
+}
+
+impl<T> Hold<T> {
+    pub fn new(value: T, frames: u32) -> Self {
+        Hold { value, frames }
+    }
+}
+
+/// Shifts start of the animation by `offset` frames. Positive offset delays the start and shows
+/// frame 0 until then, negative one skips the beginning.
+pub struct Offset<T> {
+    pub value: T,
+    pub offset: i32,
+}
+
+impl<T> Offset<T> {
+    pub fn new(value: T, offset: i32) -> Self {
+        Offset { value, offset }
+    }
+}
+
+impl<T> Speed<T> {
+    /// Frame of inner value that is shown at the frame
+    pub fn inner_frame(&self, frame: u32) -> u32 {
+        (frame as f32 * self.factor).max(0.0).floor() as u32
+    }
+}
+
+impl<T: Animatable> Animatable for Speed<T> {
+    fn evaluate(&mut self, frame: u32) {
+        self.value.evaluate(self.inner_frame(frame));
+    }
+}
+
+/// Fast playback skips frames of inner value, so markers of skipped frames fire at the next shown
+/// frame
+impl<T: HasMarkers> HasMarkers for Speed<T> {
+    fn get_markers(&self, frames: Range<u32>) -> Vec<Marker> {
+        let mut markers = vec![];
+        for frame in frames {
+            let shown = self.inner_frame(frame);
+            let from = if frame == 0 {
+                shown
+            } else {
+                self.inner_frame(frame - 1) + 1
+            };
+            for m in self.value.get_markers(from..shown + 1) {
+                markers.push(m.at(frame));
+            }
+        }
+        markers
+    }
+}
+
+impl<T: Animatable> Animatable for Reverse<T> {
+    fn evaluate(&mut self, frame: u32) {
+        let last = self.frames.saturating_sub(1);
+        self.value.evaluate(last - frame.min(last));
+    }
+}
+
+impl<T: Animatable> Animatable for PingPong<T> {
+    fn evaluate(&mut self, frame: u32) {
+        self.value.evaluate(ping_pong_frame(frame, self.frames));
+    }
+}
+
+impl<T: Animatable> Animatable for Hold<T> {
+    fn evaluate(&mut self, frame: u32) {
+        self.value
+            .evaluate(frame.min(self.frames.saturating_sub(1)));
+    }
+}
+
+impl<T: Animatable> Animatable for Offset<T> {
+    fn evaluate(&mut self, frame: u32) {
+        let frame = (frame as i64 - self.offset as i64).max(0) as u32;
+        self.value.evaluate(frame);
+    }
+}
+
+impl<T: HasMarkers> HasMarkers for Reverse<T> {
+    fn get_markers(&self, frames: Range<u32>) -> Vec<Marker> {
+        let last = self.frames.saturating_sub(1);
+        remap_markers(&self.value, frames, |f| last - f.min(last))
+    }
+}
+
+impl<T: HasMarkers> HasMarkers for PingPong<T> {
+    fn get_markers(&self, frames: Range<u32>) -> Vec<Marker> {
+        remap_markers(&self.value, frames, |f| ping_pong_frame(f, self.frames))
+    }
+}
+
+impl<T: HasMarkers> HasMarkers for Hold<T> {
+    fn get_markers(&self, frames: Range<u32>) -> Vec<Marker> {
+        let last = self.frames.saturating_sub(1);
+        remap_markers(&self.value, frames, |f| f.min(last))
+    }
+}
+
+/// Markers are moved with the start of animation, markers of skipped beginning are dropped
+impl<T: HasMarkers> HasMarkers for Offset<T> {
+    fn get_markers(&self, frames: Range<u32>) -> Vec<Marker> {
+        let shift = |f: u32| (f as i64 - self.offset as i64).max(0) as u32;
+        let inner = shift(frames.start)..shift(frames.end);
+        self.value
+            .get_markers(inner)
+            .into_iter()
+            .filter_map(|m| {
+                let frame = m.frame as i64 + self.offset as i64;
+                (frame >= 0).then(|| m.at(frame as u32))
+            })
+            .filter(|m| frames.contains(&m.frame))
+            .collect()
+    }
+}
+
+/// Changed speed repeats when whole number of own frames covers the inner cycle
+impl<T> Speed<T> {
+    /// Factor as fraction `p / q` with the least denominator
+    fn factor_fraction(&self) -> Option<(u64, u64)> {
+        let factor = self.factor as f64;
+        (1..=SPEED_MAX_DENOMINATOR).find_map(|q| {
+            let p = (factor * q as f64).round();
+            let close = (factor - p / q as f64).abs() < SPEED_TOLERANCE * factor.max(1.0);
+            (p >= 1.0 && close).then_some((p as u64, q))
+        })
+    }
+}
+
+/// Cycle is the least number of frames `n` that advances inner value by a whole number of its
+/// cycles. Frames are checked against [`Speed::inner_frame`], so the cycle is unknown when
+/// rounding of the factor breaks it.
+impl<T: HasCycle> HasCycle for Speed<T> {
+    fn cycle_frames(&self) -> Option<u32> {
+        let inner = self.value.cycle_frames()?;
+        if inner == 1 {
+            return Some(1);
+        }
+        if self.factor <= 0.0 {
+            return None;
+        }
+        let (p, q) = self.factor_fraction()?;
+        // Least n where n * p / q is multiple of inner
+        let n = inner as u64 * q / gcd(p, inner as u64 * q);
+        let n = u32::try_from(n).ok()?;
+        let shown = |frame: u32| self.inner_frame(frame) % inner;
+        let holds = (0..n.min(SPEED_CHECK_FRAMES))
+            .all(|f| f.checked_add(n).map(|g| shown(g) == shown(f)) == Some(true));
+        holds.then_some(n)
+    }
+}
+
+/// Reversed animation stays at the first frame after the end
+impl<T: HasCycle> HasCycle for Reverse<T> {
+    fn cycle_frames(&self) -> Option<u32> {
+        static_cycle(self.value.cycle_frames())
+    }
+}
+
+impl<T> HasCycle for PingPong<T> {
+    fn cycle_frames(&self) -> Option<u32> {
+        Some((2 * self.frames.saturating_sub(1)).max(1))
+    }
+}
+
+impl<T: HasCycle> HasCycle for Hold<T> {
+    fn cycle_frames(&self) -> Option<u32> {
+        static_cycle(self.value.cycle_frames())
+    }
+}
+
+/// Delayed start holds the first frame, so only skipping the beginning keeps the cycle
+impl<T: HasCycle> HasCycle for Offset<T> {
+    fn cycle_frames(&self) -> Option<u32> {
+        if self.offset <= 0 {
+            self.value.cycle_frames()
+        } else {
+            static_cycle(self.value.cycle_frames())
+        }
+    }
+}
+
+/// Cycle of animation that stops at some frame, it repeats only if it is static
+fn static_cycle(inner: Option<u32>) -> Option<u32> {
+    match inner {
+        Some(1) => Some(1),
+        _ => None,
+    }
+}
+
+forward_scene_traits!(value: Speed, Reverse, PingPong, Hold, Offset);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::Marked;
+
+    /// Value that repeats after given amount of frames
+    struct Cycled(u32);
+
+    impl HasCycle for Cycled {
+        fn cycle_frames(&self) -> Option<u32> {
+            Some(self.0)
+        }
+    }
+
+    /// Remembers the last evaluated frame
+    #[derive(Default)]
+    struct Shown(u32);
+
+    impl Animatable for Shown {
+        fn evaluate(&mut self, frame: u32) {
+            self.0 = frame;
+        }
+    }
+
+    impl HasMarkers for Shown {
+        fn get_markers(&self, _frames: Range<u32>) -> Vec<Marker> {
+            vec![]
+        }
+    }
+
+    fn marked(markers: &[(&str, u32)]) -> Marked<Shown> {
+        let markers = markers.iter().map(|(n, f)| Marker::new(n, *f)).collect();
+        Marked::new(Shown::default(), markers)
+    }
+
+    /// Inner frames shown at the first `frames` frames
+    fn shown_frames<T: Animatable>(value: &mut T, inner: fn(&T) -> u32, frames: u32) -> Vec<u32> {
+        (0..frames)
+            .map(|f| {
+                value.evaluate(f);
+                inner(value)
+            })
+            .collect()
+    }
+
+    fn names(markers: Vec<Marker>) -> Vec<(String, u32)> {
+        markers.into_iter().map(|m| (m.name, m.frame)).collect()
+    }
+
+    fn at(name: &str, frame: u32) -> (String, u32) {
+        (name.to_owned(), frame)
+    }
+
+    #[test]
+    fn reverse_frames_and_markers() {
+        let mut reverse = Reverse::new(marked(&[("a", 1), ("b", 4)]), 5);
+        let frames = shown_frames(&mut reverse, |r| r.value.value.0, 7);
+        assert_eq!(frames, vec![4, 3, 2, 1, 0, 0, 0]);
+        let markers = names(reverse.get_markers(0..8));
+        assert_eq!(markers, vec![at("b", 0), at("a", 3)]);
+        assert_eq!(names(reverse.get_markers(1..3)), vec![]);
+    }
+
+    #[test]
+    fn hold_frames_and_markers() {
+        let mut hold = Hold::new(marked(&[("a", 1), ("b", 4), ("c", 6)]), 5);
+        let frames = shown_frames(&mut hold, |h| h.value.value.0, 7);
+        assert_eq!(frames, vec![0, 1, 2, 3, 4, 4, 4]);
+        // Held frame doesn't repeat its marker and frames after the end are never shown
+        let markers = names(hold.get_markers(0..10));
+        assert_eq!(markers, vec![at("a", 1), at("b", 4)]);
+    }
+
+    #[test]
+    fn offset_frames_and_markers() {
+        let mut delayed = Offset::new(marked(&[("a", 0), ("b", 2)]), 3);
+        let frames = shown_frames(&mut delayed, |o| o.value.value.0, 6);
+        assert_eq!(frames, vec![0, 0, 0, 0, 1, 2]);
+        let markers = names(delayed.get_markers(0..10));
+        assert_eq!(markers, vec![at("a", 3), at("b", 5)]);
+        assert_eq!(names(delayed.get_markers(4..10)), vec![at("b", 5)]);
+
+        let mut skipped = Offset::new(marked(&[("a", 1), ("b", 3)]), -2);
+        let frames = shown_frames(&mut skipped, |o| o.value.value.0, 3);
+        assert_eq!(frames, vec![2, 3, 4]);
+        assert_eq!(names(skipped.get_markers(0..5)), vec![at("b", 1)]);
+    }
+
+    fn speed_cycle(factor: f32, inner: u32) -> Option<u32> {
+        Speed::new(Cycled(inner), factor).cycle_frames()
+    }
+
+    #[test]
+    fn speed_cycle_is_least_whole_advance() {
+        assert_eq!(speed_cycle(2.0, 5), Some(5));
+        assert_eq!(speed_cycle(2.0, 4), Some(2));
+        assert_eq!(speed_cycle(0.5, 4), Some(8));
+        assert_eq!(speed_cycle(1.5, 6), Some(4));
+        assert_eq!(speed_cycle(0.3, 10), Some(100));
+        assert_eq!(speed_cycle(1.0, 1), Some(1));
+    }
+
+    #[test]
+    fn speed_cycle_matches_inner_frame() {
+        for (factor, inner) in [(0.3, 10), (0.7, 12), (1.5, 6), (2.0, 5), (0.1, 3)] {
+            let speed = Speed::new(Cycled(inner), factor);
+            let n = speed.cycle_frames().unwrap();
+            for f in 0..3 * n {
+                assert_eq!(
+                    speed.inner_frame(f + n) % inner,
+                    speed.inner_frame(f) % inner,
+                    "factor {} inner {} frame {}",
+                    factor,
+                    inner,
+                    f
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn speed_cycle_unknown() {
+        assert_eq!(speed_cycle(0.0, 5), None);
+        assert_eq!(speed_cycle(std::f32::consts::PI, 5), None);
+    }
+}
@@ -0,0 +1,342 @@
+use super::animatable::Animatable;
+use super::cycle::{combine_all_cycles, combine_cycles, HasCycle};
+use super::easing::Easing;
+use crate::color::{ColorRGB, ColorRGBA};
+use crate::scene::{HasMutScene, Scene};
+use glam::f32::Quat;
+use glam::Vec3;
+
+/// Values that can be animated with [`Track`]
+pub trait Interpolate: Clone {
+    /// Value between `self` and `other`, `t` is in range 0 .. 1 but can overshoot with bouncy
+    /// easing curves.
+    fn interpolate(&self, other: &Self, t: f32) -> Self;
+}
+
+impl Interpolate for f32 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for Vec3 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self.lerp(*other, t)
+    }
+}
+
+/// Rotations are interpolated by the shortest arc
+impl Interpolate for Quat {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self.slerp(*other, t)
+    }
+}
+
+impl Interpolate for ColorRGBA {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self.lerp(other, t)
+    }
+}
+
+impl Interpolate for ColorRGB {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        let c = ColorRGBA::new(self.r, self.g, self.b, 255)
+            .lerp(&ColorRGBA::new(other.r, other.g, other.b, 255), t);
+        ColorRGB::new(c.r, c.g, c.b)
+    }
+}
+
+/// Value at the given frame of [`Track`]. Easing defines how the value changes from this
+/// keyframe to the next one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Keyframe<T> {
+    pub frame: u32,
+    pub value: T,
+    pub easing: Easing,
+}
+
+/// Sequence of keyframes that can be evaluated at any frame, not only sequentially. Frames
+/// before the first keyframe take its value and frames after the last one keep the last value,
+/// unless the track is looping.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Track<T> {
+    /// Keyframes sorted by frame
+    pub keys: Vec<Keyframe<T>>,
+    /// Restart from the beginning after the last keyframe
+    pub looping: bool,
+}
+
+impl<T> Default for Track<T> {
+    fn default() -> Self {
+        Track::new()
+    }
+}
+
+impl<T> Track<T> {
+    /// Create not looping track without keyframes
+    pub fn new() -> Self {
+        Track {
+            keys: vec![],
+            looping: false,
+        }
+    }
+
+    /// Builder style version of [`Track::add_key`]
+    pub fn with_key(mut self, frame: u32, value: T, easing: Easing) -> Self {
+        self.add_key(frame, value, easing);
+        self
+    }
+
+    /// Builder style setter of [`Track::looping`]
+    pub fn looped(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+
+    /// Insert keyframe keeping the order. Keyframe at the same frame as existing one is placed
+    /// after it, so values can jump instantly.
+    pub fn add_key(&mut self, frame: u32, value: T, easing: Easing) {
+        let i = self.keys.partition_point(|k| k.frame <= frame);
+        self.keys.insert(
+            i,
+            Keyframe {
+                frame,
+                value,
+                easing,
+            },
+        );
+    }
+
+    /// Get number of frames that takes to make single cycle, that is the frame of the last
+    /// keyframe.
+    pub fn cycle_len(&self) -> u32 {
+        self.keys.last().map(|k| k.frame).unwrap_or(0)
+    }
+}
+
+impl<T: Interpolate> Track<T> {
+    /// Get value at the frame, `None` for track without keyframes
+    pub fn sample(&self, frame: u32) -> Option<T> {
+        self.sample_at(frame as f32)
+    }
+
+    /// Get value at fractional frame, that is useful for motion blur or slow motion
+    pub fn sample_at(&self, frame: f32) -> Option<T> {
+        let cycle = self.cycle_len() as f32;
+        let frame = if self.looping && cycle > 0.0 {
+            frame.rem_euclid(cycle)
+        } else {
+            frame
+        };
+        let next = self.keys.partition_point(|k| k.frame as f32 <= frame);
+        if next == 0 {
+            return self.keys.first().map(|k| k.value.clone());
+        }
+        let prev = &self.keys[next - 1];
+        let next = match self.keys.get(next) {
+            Some(k) => k,
+            None => return Some(prev.value.clone()),
+        };
+        let t = (frame - prev.frame as f32) / (next.frame - prev.frame) as f32;
+        Some(prev.value.interpolate(&next.value, prev.easing.apply(t)))
+    }
+}
+
+/// Track bound to a property of [`Scene`]. Models and lights are referenced by index in the
+/// scene, missing ones are skipped.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SceneTrack {
+    ModelOffset(usize, Track<Vec3>),
+    ModelRotation(usize, Track<Quat>),
+    CameraEye(Track<Vec3>),
+    CameraDir(Track<Vec3>),
+    LightPosition(usize, Track<Vec3>),
+    LightColor(usize, Track<ColorRGB>),
+}
+
+impl SceneTrack {
+    /// Set bound property to the value of the track at the frame
+    pub fn apply(&self, scene: &mut Scene, frame: u32) {
+        match self {
+            SceneTrack::ModelOffset(i, track) => {
+                if let (Some(m), Some(v)) = (scene.models.get_mut(*i), track.sample(frame)) {
+                    m.offset = v;
+                }
+            }
+            SceneTrack::ModelRotation(i, track) => {
+                if let (Some(m), Some(v)) = (scene.models.get_mut(*i), track.sample(frame)) {
+                    m.rotation = v;
+                }
+            }
+            SceneTrack::CameraEye(track) => {
+                if let Some(v) = track.sample(frame) {
+                    scene.camera.eye = v;
+                }
+            }
+            SceneTrack::CameraDir(track) => {
+                if let Some(v) = track.sample(frame) {
+                    scene.camera.dir = v.normalize();
+                }
+            }
+            SceneTrack::LightPosition(i, track) => {
+                if let (Some(l), Some(v)) = (scene.lights.get_mut(*i), track.sample(frame)) {
+                    l.position = v;
+                }
+            }
+            SceneTrack::LightColor(i, track) => {
+                if let (Some(l), Some(v)) = (scene.lights.get_mut(*i), track.sample(frame)) {
+                    l.color = v;
+                }
+            }
+        }
+    }
+}
+
+/// Combinator that evaluates inner value and then overrides properties of its scene with
+/// keyframe tracks.
+pub struct Tracked<T> {
+    pub value: T,
+    pub tracks: Vec<SceneTrack>,
+}
+
+impl<T> Tracked<T> {
+    pub fn new(value: T, tracks: Vec<SceneTrack>) -> Self {
+        Tracked { value, tracks }
+    }
+}
+
+forward_scene_traits!(value: Tracked);
+
+impl<T: Animatable + HasMutScene> Animatable for Tracked<T> {
+    fn evaluate(&mut self, frame: u32) {
+        self.value.evaluate(frame);
+        let scene = self.value.get_scene_mut();
+        for track in self.tracks.iter() {
+            track.apply(scene, frame);
+        }
+    }
+}
+
+forward_markers!(value: Tracked);
+
+/// Track that doesn't loop repeats only when it is constant
+impl<T> HasCycle for Track<T> {
+    fn cycle_frames(&self) -> Option<u32> {
+        if self.looping {
+            Some(self.cycle_len().max(1))
+        } else if self.keys.len() <= 1 {
+            Some(1)
+        } else {
+            None
+        }
+    }
+}
+
+impl HasCycle for SceneTrack {
+    fn cycle_frames(&self) -> Option<u32> {
+        match self {
+            SceneTrack::ModelOffset(_, track) => track.cycle_frames(),
+            SceneTrack::ModelRotation(_, track) => track.cycle_frames(),
+            SceneTrack::CameraEye(track) => track.cycle_frames(),
+            SceneTrack::CameraDir(track) => track.cycle_frames(),
+            SceneTrack::LightPosition(_, track) => track.cycle_frames(),
+            SceneTrack::LightColor(_, track) => track.cycle_frames(),
+        }
+    }
+}
+
+impl<T: HasCycle> HasCycle for Tracked<T> {
+    fn cycle_frames(&self) -> Option<u32> {
+        combine_cycles(
+            self.value.cycle_frames(),
+            combine_all_cycles(self.tracks.iter().map(|t| t.cycle_frames())),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::Model;
+
+    fn track() -> Track<f32> {
+        Track::new()
+            .with_key(20, 30.0, Easing::Linear)
+            .with_key(10, 10.0, Easing::Linear)
+            .with_key(30, 0.0, Easing::Step)
+    }
+
+    #[test]
+    fn sample_between_keys() {
+        let t = track();
+        assert_eq!(t.sample(10), Some(10.0));
+        assert_eq!(t.sample(15), Some(20.0));
+        assert_eq!(t.sample(20), Some(30.0));
+        assert_eq!(t.sample_at(12.5), Some(15.0));
+        assert_eq!(Track::<f32>::new().sample(3), None);
+    }
+
+    #[test]
+    fn sample_clamps_outside_keys() {
+        let t = track();
+        assert_eq!(t.sample(0), Some(10.0));
+        assert_eq!(t.sample(30), Some(0.0));
+        assert_eq!(t.sample(1000), Some(0.0));
+    }
+
+    #[test]
+    fn step_and_linear_easing() {
+        let step = Track::new()
+            .with_key(0, 0.0, Easing::Step)
+            .with_key(10, 1.0, Easing::Linear);
+        assert_eq!(step.sample(9), Some(0.0));
+        assert_eq!(step.sample(10), Some(1.0));
+        let linear =
+            Track::new()
+                .with_key(0, 0.0, Easing::Linear)
+                .with_key(10, 1.0, Easing::Linear);
+        assert_eq!(linear.sample(9), Some(0.9));
+        // Key at the same frame jumps instantly
+        let jump = linear.clone().with_key(10, 5.0, Easing::Linear);
+        assert_eq!(jump.sample(10), Some(5.0));
+    }
+
+    #[test]
+    fn looping_track() {
+        let t = Track::new()
+            .with_key(0, 0.0, Easing::Linear)
+            .with_key(8, 8.0, Easing::Linear)
+            .looped();
+        assert_eq!(t.sample(4), Some(4.0));
+        assert_eq!(t.sample(8), Some(0.0));
+        assert_eq!(t.sample(13), Some(5.0));
+    }
+
+    #[test]
+    fn cycle_frames() {
+        assert_eq!(track().cycle_frames(), None);
+        assert_eq!(track().looped().cycle_frames(), Some(30));
+        assert_eq!(Track::<f32>::new().cycle_frames(), Some(1));
+        assert_eq!(Track::<f32>::new().looped().cycle_frames(), Some(1));
+        let single = Track::new().with_key(5, 1.0, Easing::Linear);
+        assert_eq!(single.cycle_frames(), Some(1));
+    }
+
+    #[test]
+    fn scene_track_sets_property() {
+        let mut scene = Scene {
+            models: vec![Model::new(glam::UVec3::ONE)],
+            ..Scene::default()
+        };
+        let track = SceneTrack::ModelOffset(
+            0,
+            Track::new()
+                .with_key(0, Vec3::ZERO, Easing::Linear)
+                .with_key(4, Vec3::new(4.0, 0.0, 8.0), Easing::Linear),
+        );
+        track.apply(&mut scene, 2);
+        assert_eq!(scene.models[0].offset, Vec3::new(2.0, 0.0, 4.0));
+        // Missing model is skipped
+        SceneTrack::ModelOffset(3, Track::new()).apply(&mut scene, 2);
+    }
+}
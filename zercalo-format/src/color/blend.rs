@@ -0,0 +1,109 @@
+use super::ColorRGBA;
+use glam::Vec3;
+
+/// Separable blend modes as in image editors and CSS `mix-blend-mode`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum BlendMode {
+    /// Top color covers bottom one
+    #[default]
+    Normal,
+    /// Darkens bottom color by top one
+    Multiply,
+    /// Lightens bottom color by top one, inverse of multiply
+    Screen,
+    /// Multiply for dark bottom colors and screen for light ones, increases contrast
+    Overlay,
+    /// Sum of colors clamped to white
+    Add,
+    /// Minimum of channels
+    Darken,
+    /// Maximum of channels
+    Lighten,
+}
+
+impl BlendMode {
+    /// Mix opaque bottom and top channels that are in range 0 .. 1.0
+    pub fn mix_channel(&self, bottom: f32, top: f32) -> f32 {
+        match self {
+            BlendMode::Normal => top,
+            BlendMode::Multiply => bottom * top,
+            BlendMode::Screen => bottom + top - bottom * top,
+            BlendMode::Overlay => {
+                if bottom <= 0.5 {
+                    2.0 * bottom * top
+                } else {
+                    1.0 - 2.0 * (1.0 - bottom) * (1.0 - top)
+                }
+            }
+            BlendMode::Add => (bottom + top).min(1.0),
+            BlendMode::Darken => bottom.min(top),
+            BlendMode::Lighten => bottom.max(top),
+        }
+    }
+
+    /// Mix opaque bottom and top colors that are in range 0 .. 1.0
+    pub fn mix(&self, bottom: Vec3, top: Vec3) -> Vec3 {
+        Vec3::new(
+            self.mix_channel(bottom.x, top.x),
+            self.mix_channel(bottom.y, top.y),
+            self.mix_channel(bottom.z, top.z),
+        )
+    }
+}
+
+impl ColorRGBA {
+    /// Draw the color on top of `bottom` with given blend mode. Alpha of both colors is taken
+    /// into account: blend mode affects only the area where both colors are present, the rest
+    /// is composed with usual "over" operation.
+    pub fn blend(&self, bottom: &ColorRGBA, mode: BlendMode) -> ColorRGBA {
+        let top = self.as_vec4();
+        let base = bottom.as_vec4();
+        let alpha = top.w + base.w * (1.0 - top.w);
+        if alpha <= 0.0 {
+            return ColorRGBA::empty();
+        }
+        let mixed = mode.mix(base.truncate(), top.truncate());
+        let top_color = top.truncate() * (1.0 - base.w) + mixed * base.w;
+        let color = (top_color * top.w + base.truncate() * base.w * (1.0 - top.w)) / alpha;
+        ColorRGBA::from_vec4(color.extend(alpha))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOTTOM: ColorRGBA = ColorRGBA::new(51, 153, 255, 255);
+    const TOP: ColorRGBA = ColorRGBA::new(204, 102, 0, 255);
+
+    #[test]
+    fn opaque_modes() {
+        let cases = [
+            (BlendMode::Normal, ColorRGBA::new(204, 102, 0, 255)),
+            (BlendMode::Multiply, ColorRGBA::new(41, 61, 0, 255)),
+            (BlendMode::Screen, ColorRGBA::new(214, 194, 255, 255)),
+            (BlendMode::Overlay, ColorRGBA::new(82, 133, 255, 255)),
+            (BlendMode::Add, ColorRGBA::new(255, 255, 255, 255)),
+            (BlendMode::Darken, ColorRGBA::new(51, 102, 0, 255)),
+            (BlendMode::Lighten, ColorRGBA::new(204, 153, 255, 255)),
+        ];
+        for (mode, expected) in cases {
+            assert_eq!(TOP.blend(&BOTTOM, mode), expected, "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn alpha_composition() {
+        // Blend mode doesn't matter over nothing and nothing over color keeps it
+        for mode in [BlendMode::Multiply, BlendMode::Screen, BlendMode::Darken] {
+            assert_eq!(TOP.blend(&ColorRGBA::empty(), mode), TOP);
+            assert_eq!(ColorRGBA::empty().blend(&BOTTOM, mode), BOTTOM);
+        }
+        assert_eq!(
+            ColorRGBA::empty().blend(&ColorRGBA::empty(), BlendMode::Normal),
+            ColorRGBA::empty()
+        );
+        let half = TOP.with_alpha(128).blend(&BOTTOM, BlendMode::Normal);
+        assert_eq!(half, ColorRGBA::new(128, 127, 127, 255));
+    }
+}
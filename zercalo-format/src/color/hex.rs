@@ -0,0 +1,141 @@
+use super::{ColorRGB, ColorRGBA};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ColorParseError {
+    #[error("Color {0} should have 3, 4, 6 or 8 hex digits")]
+    Length(String),
+    #[error("Color {0} has invalid hex digit")]
+    Digit(String),
+}
+
+impl ColorRGBA {
+    /// Parse color from hex string like `#f2b76a`, `#f2b76a80` or short `#fb7`, `#fb78`. Leading
+    /// `#` is optional and colors without alpha are opaque.
+    pub fn from_hex(hex: &str) -> Result<Self, ColorParseError> {
+        let digits = hex.trim().trim_start_matches('#');
+        let values: Option<Vec<u8>> = digits
+            .chars()
+            .map(|c| c.to_digit(16).map(|d| d as u8))
+            .collect();
+        let values = values.ok_or_else(|| ColorParseError::Digit(hex.to_owned()))?;
+        let pair = |i: usize| values[i] * 16 + values[i + 1];
+        let single = |i: usize| values[i] * 17;
+        match values.len() {
+            3 => Ok(ColorRGBA::new(single(0), single(1), single(2), 255)),
+            4 => Ok(ColorRGBA::new(single(0), single(1), single(2), single(3))),
+            6 => Ok(ColorRGBA::new(pair(0), pair(2), pair(4), 255)),
+            8 => Ok(ColorRGBA::new(pair(0), pair(2), pair(4), pair(6))),
+            _ => Err(ColorParseError::Length(hex.to_owned())),
+        }
+    }
+
+    /// Print color as hex string, alpha is skipped for opaque colors
+    pub fn to_hex(&self) -> String {
+        if self.a == 255 {
+            format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+        }
+    }
+}
+
+impl FromStr for ColorRGBA {
+    type Err = ColorParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ColorRGBA::from_hex(s)
+    }
+}
+
+impl fmt::Display for ColorRGBA {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
+
+impl ColorRGB {
+    /// Parse color from hex string, see [`ColorRGBA::from_hex`]. Alpha is dropped.
+    pub fn from_hex(hex: &str) -> Result<Self, ColorParseError> {
+        let c = ColorRGBA::from_hex(hex)?;
+        Ok(ColorRGB::new(c.r, c.g, c.b))
+    }
+
+    /// Print color as hex string like `#f2b76a`
+    pub fn to_hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+impl FromStr for ColorRGB {
+    type Err = ColorParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ColorRGB::from_hex(s)
+    }
+}
+
+impl fmt::Display for ColorRGB {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hex() {
+        assert_eq!(
+            ColorRGBA::from_hex("#fb7"),
+            Ok(ColorRGBA::new(255, 187, 119, 255))
+        );
+        assert_eq!(
+            ColorRGBA::from_hex("fb78"),
+            Ok(ColorRGBA::new(255, 187, 119, 136))
+        );
+        assert_eq!(
+            ColorRGBA::from_hex("#F2B76A"),
+            Ok(ColorRGBA::new(242, 183, 106, 255))
+        );
+        assert_eq!(
+            " #f2b76a80 ".parse::<ColorRGBA>(),
+            Ok(ColorRGBA::new(242, 183, 106, 128))
+        );
+        assert_eq!(
+            "#f2b76a80".parse::<ColorRGB>(),
+            Ok(ColorRGB::new(242, 183, 106))
+        );
+    }
+
+    #[test]
+    fn parse_invalid_hex() {
+        for hex in ["", "#", "#12", "#12345", "#1234567", "#123456789"] {
+            assert_eq!(
+                ColorRGBA::from_hex(hex),
+                Err(ColorParseError::Length(hex.to_owned()))
+            );
+        }
+        for hex in ["#ggg", "#12345z", "# 123"] {
+            assert_eq!(
+                ColorRGBA::from_hex(hex),
+                Err(ColorParseError::Digit(hex.to_owned()))
+            );
+        }
+    }
+
+    #[test]
+    fn print_hex() {
+        let opaque = ColorRGBA::new(242, 183, 6, 255);
+        let translucent = opaque.with_alpha(10);
+        assert_eq!(opaque.to_string(), "#f2b706");
+        assert_eq!(translucent.to_string(), "#f2b7060a");
+        assert_eq!(ColorRGB::new(1, 2, 3).to_string(), "#010203");
+        for c in [opaque, translucent, ColorRGBA::empty()] {
+            assert_eq!(c.to_string().parse::<ColorRGBA>(), Ok(c));
+        }
+    }
+}
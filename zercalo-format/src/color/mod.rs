@@ -1,3 +1,15 @@
+pub mod blend;
+pub mod hex;
+pub mod ramp;
+pub mod remap;
+pub mod space;
+
+pub use blend::*;
+pub use hex::*;
+pub use ramp::*;
+pub use remap::*;
+pub use space::*;
+
 use glam::{Vec3, Vec4};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -8,7 +20,7 @@ pub struct ColorRGB {
 }
 
 impl ColorRGB {
-    pub fn new(r: u8, g: u8, b: u8) -> Self {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
         ColorRGB { r, g, b }
     }
 
@@ -44,7 +56,7 @@ pub struct ColorRGBA {
 }
 
 impl ColorRGBA {
-    pub fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
         ColorRGBA { r, g, b, a }
     }
 
@@ -108,15 +120,64 @@ impl ColorRGBA {
         ColorRGBA::new(r, g, b, a)
     }
 
+    /// Convert from Vec4 where each component are in range 0 .. 1.0, values out of range are
+    /// clamped
+    pub fn from_vec4(v: Vec4) -> Self {
+        let to_u8 = |f: f32| (f * 255.0).round().clamp(0.0, 255.0) as u8;
+        ColorRGBA::new(to_u8(v.x), to_u8(v.y), to_u8(v.z), to_u8(v.w))
+    }
+
+    /// Linear interpolation of channels between two colors, `t` is in range 0 .. 1.0
+    pub fn lerp(&self, other: &ColorRGBA, t: f32) -> Self {
+        ColorRGBA::from_vec4(self.as_vec4().lerp(other.as_vec4(), t))
+    }
+
+    /// Relative luminance of color in range 0 .. 1 with Rec. 709 coefficients
+    pub fn luminance(&self) -> f32 {
+        let v = self.as_vec4();
+        0.2126 * v.x + 0.7152 * v.y + 0.0722 * v.z
+    }
+
     pub fn player1() -> Self {
-        ColorRGBA::new(240, 0, 0, 255)
+        TEAM_COLORS[0].1
     }
 
     pub fn player2() -> Self {
-        ColorRGBA::new(0, 0, 240, 255)
+        TEAM_COLORS[1].1
+    }
+
+    /// Get team color by index, indices wrap around [`TEAM_COLORS`]
+    pub fn team(index: usize) -> Self {
+        TEAM_COLORS[index % TEAM_COLORS.len()].1
+    }
+
+    /// Find team color or basic color (`white`, `black`, `empty`) by case insensitive name
+    pub fn named(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        match name.as_str() {
+            "white" => Some(ColorRGBA::white()),
+            "black" => Some(ColorRGBA::black()),
+            "empty" | "transparent" => Some(ColorRGBA::empty()),
+            _ => TEAM_COLORS
+                .iter()
+                .find(|(team, _)| *team == name)
+                .map(|(_, c)| *c),
+        }
     }
 }
 
+/// Named colors of teams, the first two are [`ColorRGBA::player1`] and [`ColorRGBA::player2`]
+pub const TEAM_COLORS: [(&str, ColorRGBA); 8] = [
+    ("red", ColorRGBA::new(240, 0, 0, 255)),
+    ("blue", ColorRGBA::new(0, 0, 240, 255)),
+    ("green", ColorRGBA::new(0, 170, 0, 255)),
+    ("yellow", ColorRGBA::new(240, 200, 0, 255)),
+    ("orange", ColorRGBA::new(240, 120, 0, 255)),
+    ("purple", ColorRGBA::new(140, 0, 200, 255)),
+    ("cyan", ColorRGBA::new(0, 200, 220, 255)),
+    ("pink", ColorRGBA::new(240, 90, 170, 255)),
+];
+
 impl Default for ColorRGBA {
     fn default() -> Self {
         ColorRGBA::new(0, 0, 0, 255)
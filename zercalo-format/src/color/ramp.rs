@@ -0,0 +1,253 @@
+use super::{ColorParseError, ColorRGBA};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ColorRampParseError {
+    #[error("Unknown interpolation {0}, expected step, linear or perceptual")]
+    Interpolation(String),
+    #[error("Color stop {0} should be a color and a position")]
+    Stop(String),
+    #[error("{0}")]
+    Color(#[from] ColorParseError),
+}
+
+/// Defines how colors between stops of [`ColorRamp`] are calculated
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum RampInterpolation {
+    /// Color of the previous stop is kept until the next one
+    Step,
+    /// Channels are interpolated linearly
+    #[default]
+    Linear,
+    /// Colors are interpolated in OKLab space, that looks more even for the eye
+    Perceptual,
+}
+
+impl RampInterpolation {
+    fn name(&self) -> &'static str {
+        match self {
+            RampInterpolation::Step => "step",
+            RampInterpolation::Linear => "linear",
+            RampInterpolation::Perceptual => "perceptual",
+        }
+    }
+}
+
+/// Color at the given position of [`ColorRamp`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorStop {
+    pub position: f32,
+    pub color: ColorRGBA,
+}
+
+/// Gradient that maps a number to color. Stops are kept sorted by position, values before the
+/// first stop take its color and values after the last stop take the last color. Alpha is
+/// interpolated as other channels.
+///
+/// Ramp can be stored as text like `linear: #6f7b9b 0, #e55829 0.5, #f9c300 1`, see
+/// [`ColorRamp::from_str`] and [`fmt::Display`] implementation.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct ColorRamp {
+    pub stops: Vec<ColorStop>,
+    pub interpolation: RampInterpolation,
+}
+
+impl ColorRamp {
+    /// Create ramp without stops
+    pub fn new(interpolation: RampInterpolation) -> Self {
+        ColorRamp {
+            stops: vec![],
+            interpolation,
+        }
+    }
+
+    /// Create ramp from list of colors with positions
+    pub fn from_stops(interpolation: RampInterpolation, stops: &[(f32, ColorRGBA)]) -> Self {
+        stops
+            .iter()
+            .fold(ColorRamp::new(interpolation), |ramp, (p, c)| {
+                ramp.with_stop(*p, *c)
+            })
+    }
+
+    /// Builder style version of [`ColorRamp::add_stop`]
+    pub fn with_stop(mut self, position: f32, color: ColorRGBA) -> Self {
+        self.add_stop(position, color);
+        self
+    }
+
+    /// Insert color stop keeping the order. Stop with the same position as existing one is
+    /// placed after it, so step ramps can make sharp borders.
+    pub fn add_stop(&mut self, position: f32, color: ColorRGBA) {
+        let i = self.stops.partition_point(|s| s.position <= position);
+        self.stops.insert(i, ColorStop { position, color });
+    }
+
+    /// Get color at the position, empty color for ramp without stops
+    pub fn sample(&self, t: f32) -> ColorRGBA {
+        let next = self.stops.partition_point(|s| s.position <= t);
+        if next == 0 {
+            return self
+                .stops
+                .first()
+                .map(|s| s.color)
+                .unwrap_or_else(ColorRGBA::empty);
+        }
+        let prev = &self.stops[next - 1];
+        let next = match self.stops.get(next) {
+            Some(s) => s,
+            None => return prev.color,
+        };
+        let k = (t - prev.position) / (next.position - prev.position);
+        match self.interpolation {
+            RampInterpolation::Step => prev.color,
+            RampInterpolation::Linear => prev.color.lerp(&next.color, k),
+            RampInterpolation::Perceptual => prev.color.lerp_oklab(&next.color, k),
+        }
+    }
+}
+
+impl FromStr for ColorRamp {
+    type Err = ColorRampParseError;
+
+    /// Parse ramp from text like `step: #f9c300 0, #e55829 0.7, #6f7b9b 1`. Interpolation is
+    /// optional and linear by default.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (interpolation, stops) = match s.split_once(':') {
+            Some((name, stops)) => {
+                let interpolation = match name.trim() {
+                    "step" => RampInterpolation::Step,
+                    "linear" => RampInterpolation::Linear,
+                    "perceptual" => RampInterpolation::Perceptual,
+                    other => return Err(ColorRampParseError::Interpolation(other.to_owned())),
+                };
+                (interpolation, stops)
+            }
+            None => (RampInterpolation::default(), s),
+        };
+        let mut ramp = ColorRamp::new(interpolation);
+        for stop in stops.split(',').filter(|s| !s.trim().is_empty()) {
+            let mut tokens = stop.split_whitespace();
+            let (color, position) = match (tokens.next(), tokens.next(), tokens.next()) {
+                (Some(c), Some(p), None) => (c, p),
+                _ => return Err(ColorRampParseError::Stop(stop.trim().to_owned())),
+            };
+            let position = position
+                .parse::<f32>()
+                .map_err(|_| ColorRampParseError::Stop(stop.trim().to_owned()))?;
+            ramp.add_stop(position, ColorRGBA::from_hex(color)?);
+        }
+        Ok(ramp)
+    }
+}
+
+impl fmt::Display for ColorRamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:", self.interpolation.name())?;
+        for (i, s) in self.stops.iter().enumerate() {
+            let sep = if i == 0 { "" } else { "," };
+            write!(f, "{} {} {}", sep, s.color.to_hex(), s.position)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLUE: ColorRGBA = ColorRGBA::new(0, 0, 200, 255);
+    const RED: ColorRGBA = ColorRGBA::new(200, 0, 0, 255);
+    const YELLOW: ColorRGBA = ColorRGBA::new(200, 200, 0, 100);
+
+    fn ramp(interpolation: RampInterpolation) -> ColorRamp {
+        ColorRamp::from_stops(interpolation, &[(1.0, YELLOW), (0.0, BLUE), (0.5, RED)])
+    }
+
+    #[test]
+    fn parse_and_display_round_trip() {
+        let text = "step: #0000c8 0, #c80000 0.5, #c8c80064 1";
+        let parsed: ColorRamp = text.parse().unwrap();
+        assert_eq!(parsed, ramp(RampInterpolation::Step));
+        assert_eq!(parsed.to_string(), text);
+
+        for interpolation in [
+            RampInterpolation::Step,
+            RampInterpolation::Linear,
+            RampInterpolation::Perceptual,
+        ] {
+            let r = ramp(interpolation);
+            assert_eq!(r.to_string().parse::<ColorRamp>(), Ok(r));
+        }
+        // Interpolation is linear by default and stops are sorted
+        let unsorted: ColorRamp = "#c80000 0.5, #0000c8 0, #c8c80064 1".parse().unwrap();
+        assert_eq!(unsorted, ramp(RampInterpolation::Linear));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            "smooth: #fff 0".parse::<ColorRamp>(),
+            Err(ColorRampParseError::Interpolation("smooth".to_owned()))
+        );
+        assert_eq!(
+            "linear: #fff".parse::<ColorRamp>(),
+            Err(ColorRampParseError::Stop("#fff".to_owned()))
+        );
+        assert_eq!(
+            "linear: #fff 0 1".parse::<ColorRamp>(),
+            Err(ColorRampParseError::Stop("#fff 0 1".to_owned()))
+        );
+        assert_eq!(
+            "linear: #fff half".parse::<ColorRamp>(),
+            Err(ColorRampParseError::Stop("#fff half".to_owned()))
+        );
+        assert_eq!(
+            "linear: #ffxx 0".parse::<ColorRamp>(),
+            Err(ColorRampParseError::Color(ColorParseError::Digit(
+                "#ffxx".to_owned()
+            )))
+        );
+        assert_eq!(
+            "".parse::<ColorRamp>().unwrap().sample(0.5),
+            ColorRGBA::empty()
+        );
+    }
+
+    #[test]
+    fn sample_step() {
+        let r = ramp(RampInterpolation::Step);
+        assert_eq!(r.sample(0.0), BLUE);
+        assert_eq!(r.sample(0.49), BLUE);
+        assert_eq!(r.sample(0.5), RED);
+        assert_eq!(r.sample(0.99), RED);
+        assert_eq!(r.sample(1.0), YELLOW);
+        assert_eq!(r.sample(-3.0), BLUE);
+        assert_eq!(r.sample(7.0), YELLOW);
+    }
+
+    #[test]
+    fn sample_linear() {
+        let r = ramp(RampInterpolation::Linear);
+        assert_eq!(r.sample(0.0), BLUE);
+        assert_eq!(r.sample(0.5), RED);
+        assert_eq!(r.sample(1.0), YELLOW);
+        assert_eq!(r.sample(0.25), ColorRGBA::new(100, 0, 100, 255));
+        assert_eq!(r.sample(0.75), ColorRGBA::new(200, 100, 0, 178));
+        assert_eq!(r.sample(-1.0), BLUE);
+        assert_eq!(r.sample(2.0), YELLOW);
+    }
+
+    #[test]
+    fn sharp_border_of_equal_stops() {
+        let r = ColorRamp::new(RampInterpolation::Linear)
+            .with_stop(0.0, BLUE)
+            .with_stop(0.5, BLUE)
+            .with_stop(0.5, RED)
+            .with_stop(1.0, RED);
+        assert_eq!(r.sample(0.4999), BLUE);
+        assert_eq!(r.sample(0.5), RED);
+    }
+}
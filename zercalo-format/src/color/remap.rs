@@ -0,0 +1,149 @@
+use super::{ColorHSV, ColorRGBA};
+
+/// Defines which colors are affected by [`RemapRule`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorMatch {
+    /// Color that differs from the given one by at most `tolerance` in each channel
+    Tolerance { color: ColorRGBA, tolerance: u8 },
+    /// Color with hue in range from `from` to `to` in degrees, range wraps around 360 when
+    /// `from` is greater than `to`. Colors with saturation below `min_saturation` are skipped,
+    /// as hue of greyish colors is unstable.
+    HueRange {
+        from: f32,
+        to: f32,
+        min_saturation: f32,
+    },
+    /// Shades of grey, that is colors with saturation not greater than `max_saturation`
+    Greyscale { max_saturation: f32 },
+}
+
+impl ColorMatch {
+    /// Check that color is affected, empty colors never match
+    pub fn matches(&self, c: &ColorRGBA) -> bool {
+        if c.is_empty() {
+            return false;
+        }
+        match self {
+            ColorMatch::Tolerance { color, tolerance } => {
+                let diff = |a: u8, b: u8| (a as i16 - b as i16).unsigned_abs() as u8;
+                diff(c.r, color.r) <= *tolerance
+                    && diff(c.g, color.g) <= *tolerance
+                    && diff(c.b, color.b) <= *tolerance
+                    && diff(c.a, color.a) <= *tolerance
+            }
+            ColorMatch::HueRange {
+                from,
+                to,
+                min_saturation,
+            } => {
+                let hsv = c.to_hsv();
+                let (from, to) = (from.rem_euclid(360.0), to.rem_euclid(360.0));
+                let in_range = if from <= to {
+                    hsv.h >= from && hsv.h <= to
+                } else {
+                    hsv.h >= from || hsv.h <= to
+                };
+                hsv.s >= *min_saturation && in_range
+            }
+            ColorMatch::Greyscale { max_saturation } => c.to_hsv().s <= *max_saturation,
+        }
+    }
+}
+
+/// Defines what matched color becomes after [`RemapRule`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorTarget {
+    /// Replace with exactly that color
+    Color(ColorRGBA),
+    /// Take hue and saturation of the color, but keep luminance and alpha of the source, so
+    /// shades of grey become shades of the color. Sources brighter than the color can get at
+    /// full value are capped at that value.
+    Shade(ColorRGBA),
+    /// Shift hue of source by given amount of degrees
+    HueShift(f32),
+}
+
+impl ColorTarget {
+    /// Calculate new color for the source one
+    pub fn apply(&self, c: &ColorRGBA) -> ColorRGBA {
+        match self {
+            ColorTarget::Color(color) => *color,
+            ColorTarget::Shade(color) => {
+                // Luminance is linear in value when hue and saturation are fixed
+                let hsv = color.to_hsv();
+                let full = ColorRGBA::from_hsv(ColorHSV::new(hsv.h, hsv.s, 1.0), 255).luminance();
+                let v = (c.luminance() / full).min(1.0);
+                ColorRGBA::from_hsv(ColorHSV::new(hsv.h, hsv.s, v), c.a)
+            }
+            ColorTarget::HueShift(degrees) => {
+                let hsv = c.to_hsv();
+                ColorRGBA::from_hsv(ColorHSV::new(hsv.h + degrees, hsv.s, hsv.v), c.a)
+            }
+        }
+    }
+}
+
+/// Rule of color remapping that is more flexible than exact replacement of colors
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RemapRule {
+    pub matcher: ColorMatch,
+    pub target: ColorTarget,
+}
+
+impl RemapRule {
+    pub fn new(matcher: ColorMatch, target: ColorTarget) -> Self {
+        RemapRule { matcher, target }
+    }
+
+    /// Get remapped color if the rule matches the color
+    pub fn apply(&self, c: &ColorRGBA) -> Option<ColorRGBA> {
+        if self.matcher.matches(c) {
+            Some(self.target.apply(c))
+        } else {
+            None
+        }
+    }
+}
+
+/// Apply the first matching rule to the color, color is unchanged if nothing matches
+pub fn remap_color(rules: &[RemapRule], c: &ColorRGBA) -> ColorRGBA {
+    rules.iter().find_map(|r| r.apply(c)).unwrap_or(*c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shade_keeps_source_luminance() {
+        for target in [
+            ColorRGBA::new(240, 0, 0, 255),
+            ColorRGBA::new(0, 0, 240, 255),
+            ColorRGBA::new(30, 120, 60, 255),
+        ] {
+            let hsv = target.to_hsv();
+            let full = ColorRGBA::from_hsv(ColorHSV::new(hsv.h, hsv.s, 1.0), 255).luminance();
+            for grey in [0u8, 8, 16, 32, 64, 128] {
+                let source = ColorRGBA::new(grey, grey, grey, 200);
+                if source.luminance() > full {
+                    continue;
+                }
+                let shaded = ColorTarget::Shade(target).apply(&source);
+                assert_eq!(shaded.a, 200);
+                assert!(
+                    (shaded.luminance() - source.luminance()).abs() < 2.0 / 255.0,
+                    "{:?} -> {:?}",
+                    source,
+                    shaded
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn shade_caps_bright_source() {
+        let blue = ColorRGBA::new(0, 0, 240, 255);
+        let shaded = ColorTarget::Shade(blue).apply(&ColorRGBA::white());
+        assert_eq!(shaded, ColorRGBA::new(0, 0, 255, 255));
+    }
+}
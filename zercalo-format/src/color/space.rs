@@ -0,0 +1,266 @@
+use super::ColorRGBA;
+use glam::Vec3;
+
+/// Color in hue (degrees in range 0 .. 360), saturation and value (both in range 0 .. 1)
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Default)]
+pub struct ColorHSV {
+    pub h: f32,
+    pub s: f32,
+    pub v: f32,
+}
+
+impl ColorHSV {
+    pub fn new(h: f32, s: f32, v: f32) -> Self {
+        ColorHSV { h, s, v }
+    }
+}
+
+/// Color in hue (degrees in range 0 .. 360), saturation and lightness (both in range 0 .. 1)
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Default)]
+pub struct ColorHSL {
+    pub h: f32,
+    pub s: f32,
+    pub l: f32,
+}
+
+impl ColorHSL {
+    pub fn new(h: f32, s: f32, l: f32) -> Self {
+        ColorHSL { h, s, l }
+    }
+}
+
+/// Color in OKLab perceptual space. Lightness `l` is in range 0 .. 1, `a` and `b` are
+/// approximately in range -0.4 .. 0.4. Equal distances in that space look like equal
+/// differences of colors, so it suits for interpolation.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Default)]
+pub struct ColorOKLab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+}
+
+impl ColorOKLab {
+    pub fn new(l: f32, a: f32, b: f32) -> Self {
+        ColorOKLab { l, a, b }
+    }
+
+    /// Linear interpolation between two colors, `t` is in range 0 .. 1.0
+    pub fn lerp(&self, other: &ColorOKLab, t: f32) -> Self {
+        ColorOKLab {
+            l: self.l + (other.l - self.l) * t,
+            a: self.a + (other.a - self.a) * t,
+            b: self.b + (other.b - self.b) * t,
+        }
+    }
+}
+
+/// Convert sRGB encoded channel in range 0 .. 1 to linear light
+#[inline]
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Convert linear light channel in range 0 .. 1 to sRGB encoding
+#[inline]
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Hue in degrees of RGB color with given maximum and minimum of channels
+fn hue(rgb: Vec3, max: f32, delta: f32) -> f32 {
+    if delta <= 0.0 {
+        0.0
+    } else if max == rgb.x {
+        60.0 * ((rgb.y - rgb.z) / delta).rem_euclid(6.0)
+    } else if max == rgb.y {
+        60.0 * ((rgb.z - rgb.x) / delta + 2.0)
+    } else {
+        60.0 * ((rgb.x - rgb.y) / delta + 4.0)
+    }
+}
+
+/// RGB color from hue in degrees, chroma and lightness offset that is added to each channel
+fn from_hue_chroma(h: f32, c: f32, m: f32) -> Vec3 {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let x = c * (1.0 - (h.rem_euclid(2.0) - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    Vec3::new(r + m, g + m, b + m)
+}
+
+fn from_rgb(rgb: Vec3, a: u8) -> ColorRGBA {
+    let to_u8 = |f: f32| (f * 255.0).round().clamp(0.0, 255.0) as u8;
+    ColorRGBA::new(to_u8(rgb.x), to_u8(rgb.y), to_u8(rgb.z), a)
+}
+
+impl ColorRGBA {
+    /// Convert color to hue, saturation and value, alpha is dropped
+    pub fn to_hsv(&self) -> ColorHSV {
+        let rgb = self.as_vec4().truncate();
+        let max = rgb.max_element();
+        let delta = max - rgb.min_element();
+        let s = if max <= 0.0 { 0.0 } else { delta / max };
+        ColorHSV::new(hue(rgb, max, delta), s, max)
+    }
+
+    /// Convert from hue, saturation and value with given alpha
+    pub fn from_hsv(hsv: ColorHSV, a: u8) -> Self {
+        let v = hsv.v.clamp(0.0, 1.0);
+        let c = v * hsv.s.clamp(0.0, 1.0);
+        from_rgb(from_hue_chroma(hsv.h, c, v - c), a)
+    }
+
+    /// Convert color to hue, saturation and lightness, alpha is dropped
+    pub fn to_hsl(&self) -> ColorHSL {
+        let rgb = self.as_vec4().truncate();
+        let max = rgb.max_element();
+        let min = rgb.min_element();
+        let delta = max - min;
+        let l = (max + min) * 0.5;
+        let s = if delta <= 0.0 {
+            0.0
+        } else {
+            delta / (1.0 - (2.0 * l - 1.0).abs())
+        };
+        ColorHSL::new(hue(rgb, max, delta), s, l)
+    }
+
+    /// Convert from hue, saturation and lightness with given alpha
+    pub fn from_hsl(hsl: ColorHSL, a: u8) -> Self {
+        let l = hsl.l.clamp(0.0, 1.0);
+        let c = (1.0 - (2.0 * l - 1.0).abs()) * hsl.s.clamp(0.0, 1.0);
+        from_rgb(from_hue_chroma(hsl.h, c, l - c * 0.5), a)
+    }
+
+    /// Convert color to OKLab perceptual space, alpha is dropped
+    pub fn to_oklab(&self) -> ColorOKLab {
+        let v = self.as_vec4();
+        let (r, g, b) = (
+            srgb_to_linear(v.x),
+            srgb_to_linear(v.y),
+            srgb_to_linear(v.z),
+        );
+        let l = (0.41222146 * r + 0.53633255 * g + 0.051445995 * b).cbrt();
+        let m = (0.2119035 * r + 0.6806995 * g + 0.10739696 * b).cbrt();
+        let s = (0.08830246 * r + 0.28171885 * g + 0.6299787 * b).cbrt();
+        ColorOKLab::new(
+            0.21045426 * l + 0.7936178 * m - 0.004072047 * s,
+            1.9779985 * l - 2.4285922 * m + 0.4505937 * s,
+            0.025904037 * l + 0.78277177 * m - 0.80867577 * s,
+        )
+    }
+
+    /// Convert from OKLab perceptual space with given alpha, colors out of sRGB gamut are
+    /// clamped
+    pub fn from_oklab(lab: ColorOKLab, a: u8) -> Self {
+        let l = (lab.l + 0.39633778 * lab.a + 0.21580376 * lab.b).powi(3);
+        let m = (lab.l - 0.105561346 * lab.a - 0.06385417 * lab.b).powi(3);
+        let s = (lab.l - 0.08948418 * lab.a - 1.2914855 * lab.b).powi(3);
+        let linear = Vec3::new(
+            4.0767417 * l - 3.3077116 * m + 0.23096994 * s,
+            -1.268438 * l + 2.6097574 * m - 0.34131938 * s,
+            -0.0041960863 * l - 0.7034186 * m + 1.7076147 * s,
+        );
+        let srgb = linear.max(Vec3::ZERO).min(Vec3::ONE);
+        from_rgb(
+            Vec3::new(
+                linear_to_srgb(srgb.x),
+                linear_to_srgb(srgb.y),
+                linear_to_srgb(srgb.z),
+            ),
+            a,
+        )
+    }
+
+    /// Interpolation between two colors in OKLab space that gives perceptually even gradient.
+    /// Alpha is interpolated linearly, `t` is in range 0 .. 1.0.
+    pub fn lerp_oklab(&self, other: &ColorRGBA, t: f32) -> Self {
+        let lab = self.to_oklab().lerp(&other.to_oklab(), t);
+        let a = self.a as f32 + (other.a as f32 - self.a as f32) * t;
+        ColorRGBA::from_oklab(lab, a.round().clamp(0.0, 255.0) as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Colors covering each sextant of hue, greys and extremes
+    fn samples() -> Vec<ColorRGBA> {
+        let steps = [0u8, 17, 90, 128, 201, 255];
+        let mut colors = vec![];
+        for r in steps {
+            for g in steps {
+                for b in steps {
+                    colors.push(ColorRGBA::new(r, g, b, 77));
+                }
+            }
+        }
+        colors
+    }
+
+    fn assert_close(a: ColorRGBA, b: ColorRGBA, tolerance: u8) {
+        let diff = |x: u8, y: u8| (x as i16 - y as i16).unsigned_abs() as u8;
+        assert!(
+            diff(a.r, b.r) <= tolerance
+                && diff(a.g, b.g) <= tolerance
+                && diff(a.b, b.b) <= tolerance,
+            "{:?} != {:?}",
+            a,
+            b
+        );
+        assert_eq!(a.a, b.a);
+    }
+
+    #[test]
+    fn hsv_round_trip() {
+        for c in samples() {
+            assert_eq!(ColorRGBA::from_hsv(c.to_hsv(), c.a), c);
+        }
+        let hsv = ColorRGBA::new(0, 255, 0, 255).to_hsv();
+        assert_eq!((hsv.h, hsv.s, hsv.v), (120.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn hsl_round_trip() {
+        for c in samples() {
+            assert_eq!(ColorRGBA::from_hsl(c.to_hsl(), c.a), c);
+        }
+        let hsl = ColorRGBA::new(0, 0, 255, 255).to_hsl();
+        assert_eq!((hsl.h, hsl.s, hsl.l), (240.0, 1.0, 0.5));
+    }
+
+    #[test]
+    fn oklab_round_trip() {
+        for c in samples() {
+            assert_close(ColorRGBA::from_oklab(c.to_oklab(), c.a), c, 1);
+        }
+        let white = ColorRGBA::white().to_oklab();
+        assert!((white.l - 1.0).abs() < 1e-3);
+        assert!(white.a.abs() < 1e-3 && white.b.abs() < 1e-3);
+        assert!(ColorRGBA::black().to_oklab().l.abs() < 1e-3);
+    }
+
+    #[test]
+    fn oklab_lerp_ends() {
+        let a = ColorRGBA::new(200, 30, 10, 0);
+        let b = ColorRGBA::new(10, 60, 220, 255);
+        assert_close(a.lerp_oklab(&b, 0.0), a, 1);
+        assert_close(b.lerp_oklab(&a, 0.0), b, 1);
+        assert_eq!(a.lerp_oklab(&b, 0.5).a, 128);
+    }
+}
@@ -0,0 +1,286 @@
+use super::mesh::{greedy_mesh, MeshColoring, MeshExportError, PaletteTexture};
+use crate::scene::{Model, Scene};
+use glam::Vec3;
+use std::fs;
+
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const GLB_VERSION: u32 = 2;
+const GLB_CHUNK_JSON: u32 = 0x4E4F534A;
+const GLB_CHUNK_BIN: u32 = 0x004E4942;
+
+const GL_FLOAT: u32 = 5126;
+const GL_UNSIGNED_INT: u32 = 5125;
+const GL_ARRAY_BUFFER: u32 = 34962;
+const GL_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const GL_NEAREST: u32 = 9728;
+
+/// Writes models into binary glTF file at given path, see [`to_glb_bytes`]
+pub fn to_glb_file(
+    path: &str,
+    models: &[Model],
+    coloring: MeshColoring,
+) -> Result<(), MeshExportError> {
+    fs::write(path, to_glb_bytes(models, coloring)?)?;
+    Ok(())
+}
+
+/// Writes all models of scene into binary glTF file, see [`to_glb_bytes`]
+pub fn scene_to_glb_file(
+    path: &str,
+    scene: &Scene,
+    coloring: MeshColoring,
+) -> Result<(), MeshExportError> {
+    to_glb_file(path, &scene.models, coloring)
+}
+
+/// Encodes models into binary glTF. Each model becomes a node with its own mesh in model local
+/// coordinates, and [`Model::offset`] with [`Model::rotation`] are kept in node transform.
+/// Palette texture is embedded into the binary buffer. glTF requires at least one mesh, so models
+/// without solid voxels give [`MeshExportError::Empty`].
+pub fn to_glb_bytes(models: &[Model], coloring: MeshColoring) -> Result<Vec<u8>, MeshExportError> {
+    let palette = match coloring {
+        MeshColoring::PaletteTexture => Some(PaletteTexture::new(models)),
+        MeshColoring::PerFace => None,
+    };
+    let mut builder = GlbBuilder::default();
+    let mut meshes = vec![];
+    let mut nodes = vec![];
+    let mut translucent = false;
+
+    for model in models.iter() {
+        let quads = greedy_mesh(model);
+        // Renderer applies offset before rotation, so node translation is rotated offset
+        let translation = model.rotation.mul_vec3(model.offset);
+        let r = model.rotation;
+        let transform = format!(
+            r#""translation":[{},{},{}],"rotation":[{},{},{},{}]"#,
+            translation.x, translation.y, translation.z, r.x, r.y, r.z, r.w
+        );
+        if quads.is_empty() {
+            nodes.push(format!("{{{}}}", transform));
+            continue;
+        }
+
+        let mut positions = vec![];
+        let mut normals = vec![];
+        let mut attributes = vec![];
+        let mut indices = vec![];
+        for (k, q) in quads.iter().enumerate() {
+            translucent |= q.color.a < 255;
+            for c in q.corners.iter() {
+                positions.push(*c);
+                normals.push(q.normal);
+                match &palette {
+                    Some(p) => {
+                        let (u, v) = p.uv(&q.color);
+                        attributes.extend_from_slice(&[u, v]);
+                    }
+                    None => attributes.extend_from_slice(&q.color.as_vec4().to_array()),
+                }
+            }
+            let base = k as u32 * 4;
+            indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+        }
+
+        let (min_vec, max_vec) = positions.iter().fold(
+            (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+            |(a, b), p| (a.min(*p), b.max(*p)),
+        );
+        let count = positions.len();
+        let position_data: Vec<f32> = positions.iter().flat_map(|p| p.to_array()).collect();
+        let normal_data: Vec<f32> = normals.iter().flat_map(|p| p.to_array()).collect();
+        let position = builder.push_accessor(
+            &f32_bytes(&position_data),
+            GL_ARRAY_BUFFER,
+            GL_FLOAT,
+            count,
+            "VEC3",
+            Some(format!(
+                r#""min":[{},{},{}],"max":[{},{},{}]"#,
+                min_vec.x, min_vec.y, min_vec.z, max_vec.x, max_vec.y, max_vec.z
+            )),
+        );
+        let normal = builder.push_accessor(
+            &f32_bytes(&normal_data),
+            GL_ARRAY_BUFFER,
+            GL_FLOAT,
+            count,
+            "VEC3",
+            None,
+        );
+        let (attribute_name, attribute_type) = match palette {
+            Some(_) => ("TEXCOORD_0", "VEC2"),
+            None => ("COLOR_0", "VEC4"),
+        };
+        let attribute = builder.push_accessor(
+            &f32_bytes(&attributes),
+            GL_ARRAY_BUFFER,
+            GL_FLOAT,
+            count,
+            attribute_type,
+            None,
+        );
+        let index_bytes: Vec<u8> = indices.iter().flat_map(|i| i.to_le_bytes()).collect();
+        let index = builder.push_accessor(
+            &index_bytes,
+            GL_ELEMENT_ARRAY_BUFFER,
+            GL_UNSIGNED_INT,
+            indices.len(),
+            "SCALAR",
+            None,
+        );
+
+        nodes.push(format!(r#"{{"mesh":{},{}}}"#, meshes.len(), transform));
+        meshes.push(format!(
+            r#"{{"primitives":[{{"attributes":{{"POSITION":{},"NORMAL":{},"{}":{}}},"indices":{},"material":0}}]}}"#,
+            position, normal, attribute_name, attribute, index
+        ));
+    }
+
+    if meshes.is_empty() {
+        return Err(MeshExportError::Empty);
+    }
+
+    let alpha_mode = if translucent { "BLEND" } else { "OPAQUE" };
+    let mut extra = String::new();
+    let material = match &palette {
+        Some(p) => {
+            let image_view = builder.push_view(&p.to_png()?, None);
+            extra = format!(
+                r#","images":[{{"bufferView":{},"mimeType":"image/png"}}],"samplers":[{{"magFilter":{},"minFilter":{}}}],"textures":[{{"sampler":0,"source":0}}]"#,
+                image_view, GL_NEAREST, GL_NEAREST
+            );
+            format!(
+                r#"{{"pbrMetallicRoughness":{{"baseColorTexture":{{"index":0}},"metallicFactor":0,"roughnessFactor":1}},"alphaMode":"{}"}}"#,
+                alpha_mode
+            )
+        }
+        None => format!(
+            r#"{{"pbrMetallicRoughness":{{"metallicFactor":0,"roughnessFactor":1}},"alphaMode":"{}"}}"#,
+            alpha_mode
+        ),
+    };
+
+    let node_ids: Vec<String> = (0..nodes.len()).map(|i| i.to_string()).collect();
+    let json = format!(
+        r#"{{"asset":{{"version":"2.0","generator":"zercalo"}},"scene":0,"scenes":[{{"nodes":[{}]}}],"nodes":[{}],"meshes":[{}],"materials":[{}],"accessors":[{}],"bufferViews":[{}],"buffers":[{{"byteLength":{}}}]{}}}"#,
+        node_ids.join(","),
+        nodes.join(","),
+        meshes.join(","),
+        material,
+        builder.accessors.join(","),
+        builder.views.join(","),
+        builder.bin.len(),
+        extra
+    );
+    Ok(builder.finish(json))
+}
+
+/// Accumulates binary buffer with its views and accessors
+#[derive(Default)]
+struct GlbBuilder {
+    bin: Vec<u8>,
+    views: Vec<String>,
+    accessors: Vec<String>,
+}
+
+impl GlbBuilder {
+    /// Append data to binary buffer and return index of buffer view
+    fn push_view(&mut self, data: &[u8], target: Option<u32>) -> usize {
+        let offset = self.bin.len();
+        self.bin.extend_from_slice(data);
+        pad_to_four(&mut self.bin, 0);
+        let target = target
+            .map(|t| format!(r#","target":{}"#, t))
+            .unwrap_or_default();
+        self.views.push(format!(
+            r#"{{"buffer":0,"byteOffset":{},"byteLength":{}{}}}"#,
+            offset,
+            data.len(),
+            target
+        ));
+        self.views.len() - 1
+    }
+
+    /// Append data to binary buffer and return index of accessor for it
+    fn push_accessor(
+        &mut self,
+        data: &[u8],
+        target: u32,
+        component_type: u32,
+        count: usize,
+        accessor_type: &str,
+        bounds: Option<String>,
+    ) -> usize {
+        let view = self.push_view(data, Some(target));
+        let bounds = bounds.map(|b| format!(",{}", b)).unwrap_or_default();
+        self.accessors.push(format!(
+            r#"{{"bufferView":{},"componentType":{},"count":{},"type":"{}"{}}}"#,
+            view, component_type, count, accessor_type, bounds
+        ));
+        self.accessors.len() - 1
+    }
+
+    /// Pack JSON and binary chunks into GLB container
+    fn finish(self, json: String) -> Vec<u8> {
+        let mut json = json.into_bytes();
+        pad_to_four(&mut json, b' ');
+        let mut bin = self.bin;
+        pad_to_four(&mut bin, 0);
+
+        let total = 12 + 8 + json.len() + 8 + bin.len();
+        let mut out = Vec::with_capacity(total);
+        out.extend_from_slice(GLB_MAGIC);
+        out.extend_from_slice(&GLB_VERSION.to_le_bytes());
+        out.extend_from_slice(&(total as u32).to_le_bytes());
+        out.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        out.extend_from_slice(&GLB_CHUNK_JSON.to_le_bytes());
+        out.extend_from_slice(&json);
+        out.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+        out.extend_from_slice(&GLB_CHUNK_BIN.to_le_bytes());
+        out.extend_from_slice(&bin);
+        out
+    }
+}
+
+fn pad_to_four(data: &mut Vec<u8>, value: u8) {
+    let padded = (data.len() + 3) & !3;
+    data.resize(padded, value);
+}
+
+fn f32_bytes(data: &[f32]) -> Vec<u8> {
+    data.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::ColorRGBA;
+    use glam::UVec3;
+
+    #[test]
+    fn empty_models_are_error() {
+        let empty = Model::new(UVec3::new(2, 2, 2));
+        for coloring in [MeshColoring::PerFace, MeshColoring::PaletteTexture] {
+            assert!(matches!(
+                to_glb_bytes(&[], coloring),
+                Err(MeshExportError::Empty)
+            ));
+            assert!(matches!(
+                to_glb_bytes(std::slice::from_ref(&empty), coloring),
+                Err(MeshExportError::Empty)
+            ));
+        }
+    }
+
+    #[test]
+    fn solid_model_has_mesh_and_buffer() {
+        let model = Model::from_function(UVec3::ONE, |_| ColorRGBA::new(255, 0, 0, 255));
+        let glb = to_glb_bytes(&[model], MeshColoring::PerFace).unwrap();
+        assert_eq!(&glb[0..4], GLB_MAGIC);
+        let json_len = u32::from_le_bytes([glb[12], glb[13], glb[14], glb[15]]) as usize;
+        let json = std::str::from_utf8(&glb[20..20 + json_len]).unwrap();
+        assert!(json.contains(r#""meshes":[{"primitives""#));
+        assert!(!json.contains(r#""byteLength":0}]"#));
+    }
+}
@@ -0,0 +1,220 @@
+use super::vox::build_palette;
+use crate::color::ColorRGBA;
+use crate::scene::Model;
+use glam::{const_vec3, IVec3, Vec3};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Width of palette texture, each color takes single pixel
+pub const PALETTE_TEXTURE_WIDTH: u32 = 256;
+
+#[derive(Debug, Error)]
+pub enum MeshExportError {
+    #[error("Failed to write file: {0}")]
+    File(#[from] std::io::Error),
+    #[error("Failed to encode PNG: {0}")]
+    Png(#[from] png::EncodingError),
+    #[error("Nothing to export, models have no solid voxels")]
+    Empty,
+}
+
+/// Defines how colors of voxels are stored in exported mesh
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MeshColoring {
+    /// Each face carries its own color as material (OBJ) or vertex color (glTF)
+    PerFace,
+    /// Colors are stored in palette texture and faces refer to it with texture coordinates
+    PaletteTexture,
+}
+
+/// Rectangle that covers several coplanar faces of voxels with the same color
+#[derive(Clone, Debug)]
+pub struct Quad {
+    /// Corners in counter clockwise order when looking against the normal
+    pub corners: [Vec3; 4],
+    pub normal: Vec3,
+    pub color: ColorRGBA,
+}
+
+impl Quad {
+    /// Index of axis aligned normal in order +X, -X, +Y, -Y, +Z, -Z
+    pub fn normal_index(&self) -> usize {
+        let axis = self.normal.abs().max_element();
+        let (i, sign) = if self.normal.x.abs() == axis {
+            (0, self.normal.x)
+        } else if self.normal.y.abs() == axis {
+            (1, self.normal.y)
+        } else {
+            (2, self.normal.z)
+        };
+        i * 2 + (sign < 0.0) as usize
+    }
+}
+
+/// Axis aligned normals in order of [`Quad::normal_index`]
+pub const QUAD_NORMALS: [Vec3; 6] = [
+    Vec3::X,
+    const_vec3!([-1.0, 0.0, 0.0]),
+    Vec3::Y,
+    const_vec3!([0.0, -1.0, 0.0]),
+    Vec3::Z,
+    const_vec3!([0.0, 0.0, -1.0]),
+];
+
+/// Get color of voxel as it is rendered, empty outside of the model
+fn rendered_color(model: &Model, p: IVec3) -> ColorRGBA {
+    if p.min_element() < 0 || p.cmpge(model.size.as_ivec3()).any() {
+        return ColorRGBA::empty();
+    }
+    model.get_rendered_voxel(p.as_uvec3())
+}
+
+/// Convert model to quads in model local coordinates. Visible faces of voxels are merged into
+/// largest rectangles of the same color slice by slice.
+pub fn greedy_mesh(model: &Model) -> Vec<Quad> {
+    let mut quads = vec![];
+    if model.voxels.is_empty() {
+        return quads;
+    }
+    let dims = model.size.as_ivec3();
+    for d in 0..3 {
+        let u = (d + 1) % 3;
+        let v = (d + 2) % 3;
+        let width = dims[u] as usize;
+        let height = dims[v] as usize;
+        let mut mask: Vec<Option<(ColorRGBA, bool)>> = vec![None; width * height];
+
+        // Slice between layers `layer` and `layer + 1`
+        for layer in -1..dims[d] {
+            for j in 0..height {
+                for i in 0..width {
+                    let mut a = IVec3::ZERO;
+                    a[d] = layer;
+                    a[u] = i as i32;
+                    a[v] = j as i32;
+                    let mut b = a;
+                    b[d] += 1;
+                    let ca = rendered_color(model, a);
+                    let cb = rendered_color(model, b);
+                    mask[i + j * width] = match (ca.is_empty(), cb.is_empty()) {
+                        (false, true) => Some((ca, true)),
+                        (true, false) => Some((cb, false)),
+                        _ => None,
+                    };
+                }
+            }
+
+            for j in 0..height {
+                let mut i = 0;
+                while i < width {
+                    let cell = match mask[i + j * width] {
+                        Some(cell) => cell,
+                        None => {
+                            i += 1;
+                            continue;
+                        }
+                    };
+                    let mut w = 1;
+                    while i + w < width && mask[i + w + j * width] == Some(cell) {
+                        w += 1;
+                    }
+                    let mut h = 1;
+                    'grow: while j + h < height {
+                        for k in 0..w {
+                            if mask[i + k + (j + h) * width] != Some(cell) {
+                                break 'grow;
+                            }
+                        }
+                        h += 1;
+                    }
+
+                    let mut base = Vec3::ZERO;
+                    base[d] = (layer + 1) as f32;
+                    base[u] = i as f32;
+                    base[v] = j as f32;
+                    let mut du = Vec3::ZERO;
+                    du[u] = w as f32;
+                    let mut dv = Vec3::ZERO;
+                    dv[v] = h as f32;
+                    let mut normal = Vec3::ZERO;
+                    let (color, positive) = cell;
+                    normal[d] = if positive { 1.0 } else { -1.0 };
+                    let corners = if positive {
+                        [base, base + du, base + du + dv, base + dv]
+                    } else {
+                        [base, base + dv, base + du + dv, base + du]
+                    };
+                    quads.push(Quad {
+                        corners,
+                        normal,
+                        color,
+                    });
+
+                    for l in 0..h {
+                        for k in 0..w {
+                            mask[i + k + (j + l) * width] = None;
+                        }
+                    }
+                    i += w;
+                }
+            }
+        }
+    }
+    quads
+}
+
+/// Palette of colors that are visible on models with texture coordinates of each color
+pub struct PaletteTexture {
+    pub colors: Vec<ColorRGBA>,
+    pub mapping: HashMap<ColorRGBA, u8>,
+}
+
+impl PaletteTexture {
+    /// Collect rendered colors of models, quantizing them if there are more than 255 colors
+    pub fn new(models: &[Model]) -> Self {
+        let recolored: Vec<Model> = models
+            .iter()
+            .map(|m| Model {
+                voxels: m.voxels.iter().map(|v| m.rendered_color(v)).collect(),
+                size: m.size,
+                ..Model::default()
+            })
+            .collect();
+        let (colors, mapping) = build_palette(&recolored);
+        PaletteTexture { colors, mapping }
+    }
+
+    /// Texture coordinate of the center of color pixel, `color` is a color of models
+    pub fn uv(&self, color: &ColorRGBA) -> (f32, f32) {
+        let i = self.mapping.get(color).copied().unwrap_or(0);
+        PaletteTexture::index_uv(i as usize)
+    }
+
+    /// Texture coordinate of the center of pixel with given index in palette
+    pub fn index_uv(index: usize) -> (f32, f32) {
+        ((index as f32 + 0.5) / PALETTE_TEXTURE_WIDTH as f32, 0.5)
+    }
+
+    /// Encode palette as PNG image of single row
+    pub fn to_png(&self) -> Result<Vec<u8>, MeshExportError> {
+        let mut data = vec![0; PALETTE_TEXTURE_WIDTH as usize * 4];
+        for (i, c) in self.colors.iter().enumerate() {
+            data[i * 4..i * 4 + 4].copy_from_slice(&[c.r, c.g, c.b, c.a]);
+        }
+        let mut out = vec![];
+        {
+            let mut encoder = png::Encoder::new(&mut out, PALETTE_TEXTURE_WIDTH, 1);
+            encoder.set_color(png::ColorType::RGBA);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(&data)?;
+        }
+        Ok(out)
+    }
+}
+
+/// Transform point from model local coordinates to world space in the same way renderer does
+#[inline]
+pub fn model_to_world(model: &Model, p: Vec3) -> Vec3 {
+    model.rotation.mul_vec3(p + model.offset)
+}
@@ -0,0 +1,4 @@
+pub mod gltf;
+pub mod mesh;
+pub mod obj;
+pub mod vox;
@@ -0,0 +1,168 @@
+use super::mesh::{
+    greedy_mesh, model_to_world, MeshColoring, MeshExportError, PaletteTexture, QUAD_NORMALS,
+};
+use crate::color::ColorRGBA;
+use crate::scene::{Model, Scene};
+use std::collections::BTreeSet;
+use std::fmt::Write as FmtWrite;
+use std::fs;
+use std::path::Path;
+
+/// Writes models into Wavefront OBJ file at given path with MTL file next to it. Each model
+/// becomes separate object with vertices in world space. With [`MeshColoring::PaletteTexture`]
+/// palette PNG is written next to OBJ file with `_palette` suffix.
+pub fn to_obj_files(
+    path: &str,
+    models: &[Model],
+    coloring: MeshColoring,
+) -> Result<(), MeshExportError> {
+    let obj_path = Path::new(path);
+    let stem = obj_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("model");
+    let mtl_name = format!("{}.mtl", stem);
+    let palette_name = format!("{}_palette.png", stem);
+
+    let palette = match coloring {
+        MeshColoring::PaletteTexture => Some(PaletteTexture::new(models)),
+        MeshColoring::PerFace => None,
+    };
+    let (obj, mtl) = to_obj_strings(models, &mtl_name, &palette_name, palette.as_ref());
+
+    fs::write(obj_path, obj)?;
+    fs::write(obj_path.with_file_name(&mtl_name), mtl)?;
+    if let Some(palette) = palette {
+        fs::write(obj_path.with_file_name(&palette_name), palette.to_png()?)?;
+    }
+    Ok(())
+}
+
+/// Writes all models of scene into OBJ file, see [`to_obj_files`]
+pub fn scene_to_obj_files(
+    path: &str,
+    scene: &Scene,
+    coloring: MeshColoring,
+) -> Result<(), MeshExportError> {
+    to_obj_files(path, &scene.models, coloring)
+}
+
+/// Make contents of OBJ and MTL files. When palette is given faces are textured with it,
+/// otherwise there is a material for each color.
+pub fn to_obj_strings(
+    models: &[Model],
+    mtl_name: &str,
+    palette_name: &str,
+    palette: Option<&PaletteTexture>,
+) -> (String, String) {
+    let mut obj = String::new();
+    let mut mtl = String::new();
+    let mut colors = BTreeSet::new();
+    writeln!(obj, "mtllib {}", mtl_name).unwrap();
+    if let Some(palette) = palette {
+        for k in 0..palette.colors.len() {
+            let (u, v) = PaletteTexture::index_uv(k);
+            writeln!(obj, "vt {} {}", u, v).unwrap();
+        }
+    }
+
+    let mut vertex_base = 1;
+    let mut normal_base = 1;
+    for (i, model) in models.iter().enumerate() {
+        let mut quads = greedy_mesh(model);
+        if quads.is_empty() {
+            continue;
+        }
+        quads.sort_by_key(|q| q.color);
+        writeln!(obj, "o model_{}", i).unwrap();
+        for n in QUAD_NORMALS.iter() {
+            let n = model.rotation.mul_vec3(*n);
+            writeln!(obj, "vn {} {} {}", n.x, n.y, n.z).unwrap();
+        }
+        for q in quads.iter() {
+            for c in q.corners.iter() {
+                let p = model_to_world(model, *c);
+                writeln!(obj, "v {} {} {}", p.x, p.y, p.z).unwrap();
+            }
+        }
+
+        let mut current_material = None;
+        for (k, q) in quads.iter().enumerate() {
+            let material = match palette {
+                Some(_) => "palette".to_owned(),
+                None => material_name(&q.color),
+            };
+            if current_material.as_ref() != Some(&material) {
+                writeln!(obj, "usemtl {}", material).unwrap();
+                current_material = Some(material);
+            }
+            colors.insert(q.color);
+
+            let vertex = |corner: usize| vertex_base + k * 4 + corner;
+            let normal = normal_base + q.normal_index();
+            let texture =
+                palette.map(|p| p.mapping.get(&q.color).copied().unwrap_or(0) as usize + 1);
+            let reference = |corner: usize| match texture {
+                Some(t) => format!("{}/{}/{}", vertex(corner), t, normal),
+                None => format!("{}//{}", vertex(corner), normal),
+            };
+            writeln!(obj, "f {} {} {}", reference(0), reference(1), reference(2)).unwrap();
+            writeln!(obj, "f {} {} {}", reference(0), reference(2), reference(3)).unwrap();
+        }
+        vertex_base += quads.len() * 4;
+        normal_base += QUAD_NORMALS.len();
+    }
+
+    if palette.is_some() {
+        writeln!(mtl, "newmtl palette").unwrap();
+        writeln!(mtl, "Kd 1 1 1").unwrap();
+        writeln!(mtl, "map_Kd {}", palette_name).unwrap();
+    } else {
+        for c in colors.iter() {
+            let v = c.as_vec4();
+            writeln!(mtl, "newmtl {}", material_name(c)).unwrap();
+            writeln!(mtl, "Kd {} {} {}", v.x, v.y, v.z).unwrap();
+            writeln!(mtl, "d {}", v.w).unwrap();
+        }
+    }
+    (obj, mtl)
+}
+
+fn material_name(c: &ColorRGBA) -> String {
+    format!("color_{:02x}{:02x}{:02x}{:02x}", c.r, c.g, c.b, c.a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::UVec3;
+
+    fn texture_coords(obj: &str) -> Vec<(f32, f32)> {
+        obj.lines()
+            .filter_map(|l| l.strip_prefix("vt "))
+            .map(|l| {
+                let mut uv = l.split(' ').map(|c| c.parse::<f32>().unwrap());
+                (uv.next().unwrap(), uv.next().unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn quantized_palette_has_distinct_texture_coords() {
+        let model = Model::from_function(UVec3::new(20, 20, 1), |p| {
+            ColorRGBA::new(p.x as u8 * 12, p.y as u8 * 12, 100, 255)
+        });
+        let palette = PaletteTexture::new(std::slice::from_ref(&model));
+        assert!(palette.colors.len() > 1);
+        let (obj, _) = to_obj_strings(&[model], "m.mtl", "m_palette.png", Some(&palette));
+
+        let coords = texture_coords(&obj);
+        assert_eq!(coords.len(), palette.colors.len());
+        for (k, (u, v)) in coords.iter().enumerate() {
+            assert_eq!((*u, *v), PaletteTexture::index_uv(k));
+        }
+        let mut us: Vec<u32> = coords.iter().map(|(u, _)| u.to_bits()).collect();
+        us.dedup();
+        assert_eq!(us.len(), coords.len());
+    }
+}
@@ -0,0 +1,414 @@
+use crate::color::ColorRGBA;
+use crate::scene::Model;
+use glam::f32::Quat;
+use glam::{Mat3, UVec3};
+use log::*;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use thiserror::Error;
+
+/// Version of VOX format that MagicaVoxel writes and we mimic
+const VOX_VERSION: i32 = 150;
+/// Maximum size of model along any axis that VOX format can address
+const VOX_MAX_SIZE: u32 = 256;
+/// Maximum amount of colors in VOX palette. Index 0 is reserved for empty voxel.
+pub const VOX_PALETTE_SIZE: usize = 255;
+
+#[derive(Debug, Error)]
+pub enum VoxExportError {
+    #[error("Nothing to export, models list is empty")]
+    NoModels,
+    #[error("Model {0} has size {1} that exceeds VOX limit of 256 voxels per axis")]
+    TooLarge(usize, UVec3),
+    #[error("Failed to write file: {0}")]
+    File(#[from] std::io::Error),
+}
+
+/// Writes given models into VOX file at specified path. Each model becomes a separate shape node
+/// positioned by its [`Model::offset`].
+pub fn to_vox_file(path: &str, models: &[Model]) -> Result<(), VoxExportError> {
+    let bytes = to_vox_bytes(models)?;
+    let file = File::create(Path::new(path))?;
+    let mut w = BufWriter::new(file);
+    w.write_all(&bytes)?;
+    w.flush()?;
+    Ok(())
+}
+
+/// Encodes given models into contents of VOX file.
+///
+/// Colors of all models are collected into single palette. If there are more than 255 unique
+/// colors, the palette is quantized with [`build_palette`]. Axes are swapped back in the same way
+/// as [`crate::import::vox::from_vox_model`] swaps them, so importing the result with
+/// [`crate::import::vox::from_vox_scene_slice`] gives the same voxels, offsets and rotations.
+pub fn to_vox_bytes(models: &[Model]) -> Result<Vec<u8>, VoxExportError> {
+    if models.is_empty() {
+        return Err(VoxExportError::NoModels);
+    }
+    for (i, m) in models.iter().enumerate() {
+        if m.size.max_element() > VOX_MAX_SIZE {
+            return Err(VoxExportError::TooLarge(i, m.size));
+        }
+    }
+
+    let (palette, mapping) = build_palette(models);
+
+    let mut children = vec![];
+    for m in models.iter() {
+        write_model_chunks(&mut children, m, &mapping);
+    }
+    write_scene_graph(&mut children, models);
+    write_palette_chunk(&mut children, &palette);
+
+    let mut out = vec![];
+    out.extend_from_slice(b"VOX ");
+    write_i32(&mut out, VOX_VERSION);
+    write_chunk(&mut out, b"MAIN", &[], &children);
+    Ok(out)
+}
+
+/// Collect colors of all non empty voxels into palette of at most [`VOX_PALETTE_SIZE`] colors.
+///
+/// Returns the palette and mapping from each original color to index in the palette. When models
+/// have too many unique colors, they are reduced with median cut quantization weighted by amount of
+/// voxels of each color.
+pub fn build_palette(models: &[Model]) -> (Vec<ColorRGBA>, HashMap<ColorRGBA, u8>) {
+    let mut counts: HashMap<ColorRGBA, usize> = HashMap::new();
+    for m in models.iter() {
+        for v in m.voxels.iter().filter(|v| !v.is_empty()) {
+            *counts.entry(*v).or_insert(0) += 1;
+        }
+    }
+    let mut colors: Vec<(ColorRGBA, usize)> = counts.into_iter().collect();
+    // Sorting makes resulted palette stable between runs
+    colors.sort();
+
+    if colors.len() <= VOX_PALETTE_SIZE {
+        let palette: Vec<ColorRGBA> = colors.iter().map(|(c, _)| *c).collect();
+        let mapping = palette
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (*c, i as u8))
+            .collect();
+        return (palette, mapping);
+    }
+
+    debug!(
+        "Quantizing {} unique colors to VOX palette of {}",
+        colors.len(),
+        VOX_PALETTE_SIZE
+    );
+    let mut boxes = vec![colors];
+    while boxes.len() < VOX_PALETTE_SIZE {
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.len() > 1)
+            .map(|(i, b)| (i, widest_channel(b)))
+            .max_by_key(|(_, (_, range))| *range);
+        let (i, (channel, _)) = match widest {
+            Some(v) => v,
+            None => break,
+        };
+        let mut cut = boxes.swap_remove(i);
+        cut.sort_by_key(|(c, _)| channel_value(c, channel));
+        let total: usize = cut.iter().map(|(_, n)| n).sum();
+        let mut accum = 0;
+        let mut median = 1;
+        for (j, (_, n)) in cut.iter().enumerate() {
+            accum += n;
+            if accum * 2 >= total {
+                median = (j + 1).clamp(1, cut.len() - 1);
+                break;
+            }
+        }
+        let upper = cut.split_off(median);
+        boxes.push(cut);
+        boxes.push(upper);
+    }
+
+    let mut palette = vec![];
+    let mut mapping = HashMap::new();
+    for (i, b) in boxes.iter().enumerate() {
+        palette.push(average_color(b));
+        for (c, _) in b.iter() {
+            mapping.insert(*c, i as u8);
+        }
+    }
+    (palette, mapping)
+}
+
+#[inline]
+fn channel_value(c: &ColorRGBA, channel: usize) -> u8 {
+    match channel {
+        0 => c.r,
+        1 => c.g,
+        2 => c.b,
+        _ => c.a,
+    }
+}
+
+/// Find channel with largest spread of values inside the box of colors
+fn widest_channel(colors: &[(ColorRGBA, usize)]) -> (usize, u8) {
+    (0..4)
+        .map(|channel| {
+            let values = colors.iter().map(|(c, _)| channel_value(c, channel));
+            let minv = values.clone().min().unwrap_or(0);
+            let maxv = values.max().unwrap_or(0);
+            (channel, maxv - minv)
+        })
+        .max_by_key(|(_, range)| *range)
+        .unwrap_or((0, 0))
+}
+
+/// Average color of the box weighted by amount of voxels
+fn average_color(colors: &[(ColorRGBA, usize)]) -> ColorRGBA {
+    let total: usize = colors.iter().map(|(_, n)| n).sum();
+    let mut acc = [0usize; 4];
+    for (c, n) in colors.iter() {
+        for (channel, a) in acc.iter_mut().enumerate() {
+            *a += channel_value(c, channel) as usize * n;
+        }
+    }
+    let avg = |channel: usize| ((acc[channel] + total / 2) / total.max(1)) as u8;
+    ColorRGBA::new(avg(0), avg(1), avg(2), avg(3))
+}
+
+/// Write SIZE and XYZI chunks of single model with swapped Y and Z axes
+fn write_model_chunks(out: &mut Vec<u8>, model: &Model, mapping: &HashMap<ColorRGBA, u8>) {
+    let mut size = vec![];
+    write_i32(&mut size, model.size.x as i32);
+    write_i32(&mut size, model.size.z as i32);
+    write_i32(&mut size, model.size.y as i32);
+    write_chunk(out, b"SIZE", &size, &[]);
+
+    let sx = model.size.x as usize;
+    let sy = model.size.y as usize;
+    let mut voxels = vec![];
+    let mut amount = 0;
+    for (i, v) in model.voxels.iter().enumerate() {
+        if v.is_empty() {
+            continue;
+        }
+        let x = i % sx;
+        let y = (i / sx) % sy;
+        let z = i / (sx * sy);
+        // Index 0 in VOX means empty voxel, so palette is shifted by one
+        voxels.extend_from_slice(&[x as u8, z as u8, y as u8, mapping[v] + 1]);
+        amount += 1;
+    }
+    let mut xyzi = vec![];
+    write_i32(&mut xyzi, amount);
+    xyzi.extend_from_slice(&voxels);
+    write_chunk(out, b"XYZI", &xyzi, &[]);
+}
+
+/// Write scene graph where root transform holds a group with a transform and shape per model.
+/// MagicaVoxel places model by its center, so offsets are converted from the minimal corner.
+/// Only rotations by quarter turns can be written, other rotations are dropped.
+fn write_scene_graph(out: &mut Vec<u8>, models: &[Model]) {
+    let mut root = vec![];
+    write_i32(&mut root, 0);
+    write_dict(&mut root, &[]);
+    write_i32(&mut root, 1);
+    write_i32(&mut root, -1);
+    write_i32(&mut root, -1);
+    write_i32(&mut root, 1);
+    write_dict(&mut root, &[]);
+    write_chunk(out, b"nTRN", &root, &[]);
+
+    let mut group = vec![];
+    write_i32(&mut group, 1);
+    write_dict(&mut group, &[]);
+    write_i32(&mut group, models.len() as i32);
+    for i in 0..models.len() {
+        write_i32(&mut group, 2 + 2 * i as i32);
+    }
+    write_chunk(out, b"nGRP", &group, &[]);
+
+    for (i, m) in models.iter().enumerate() {
+        let (rotation, vox_rotation) = match quat_to_vox_rotation(m.rotation) {
+            Some(r) => (m.rotation, r),
+            None => {
+                warn!(
+                    "Rotation of model {} is not by quarter turns, it is not exported",
+                    i
+                );
+                (Quat::IDENTITY, VOX_IDENTITY_ROTATION)
+            }
+        };
+        let pivot = (m.size / 2).as_vec3();
+        let center = rotation
+            .mul_vec3(m.offset.round() + pivot)
+            .round()
+            .as_ivec3();
+        let translation = format!("{} {} {}", center.x, center.z, center.y);
+        let vox_rotation = vox_rotation.to_string();
+
+        let node_id = 2 + 2 * i as i32;
+        let mut transform = vec![];
+        write_i32(&mut transform, node_id);
+        write_dict(&mut transform, &[]);
+        write_i32(&mut transform, node_id + 1);
+        write_i32(&mut transform, -1);
+        write_i32(&mut transform, 0);
+        write_i32(&mut transform, 1);
+        write_dict(
+            &mut transform,
+            &[("_t", &translation), ("_r", &vox_rotation)],
+        );
+        write_chunk(out, b"nTRN", &transform, &[]);
+
+        let mut shape = vec![];
+        write_i32(&mut shape, node_id + 1);
+        write_dict(&mut shape, &[]);
+        write_i32(&mut shape, 1);
+        write_i32(&mut shape, i as i32);
+        write_dict(&mut shape, &[]);
+        write_chunk(out, b"nSHP", &shape, &[]);
+    }
+}
+
+/// `_r` attribute of identity rotation: rows pick X and Y, all signs are positive
+const VOX_IDENTITY_ROTATION: u8 = 0b0100;
+
+/// Encode rotation by quarter turns as `_r` attribute in VOX axes, where Y and Z are swapped.
+/// Bits 0-1 and 2-3 are indices of non zero entries in the first and second rows, bits 4-6 are
+/// signs of the rows. Returns `None` for other rotations.
+fn quat_to_vox_rotation(rotation: Quat) -> Option<u8> {
+    let m = Mat3::from_quat(rotation);
+    let swap = |i: usize| [0, 2, 1][i];
+    let mut r = 0;
+    for row in 0..3 {
+        let entries: Vec<f32> = (0..3).map(|col| m.col(swap(col))[swap(row)]).collect();
+        let col = entries.iter().position(|e| e.abs() > 0.5)?;
+        let on_axis = entries
+            .iter()
+            .enumerate()
+            .all(|(j, e)| (e.abs() - if j == col { 1.0 } else { 0.0 }).abs() < 1e-3);
+        if !on_axis {
+            return None;
+        }
+        if row < 2 {
+            r |= (col as u8) << (2 * row);
+        }
+        if entries[col] < 0.0 {
+            r |= 1 << (4 + row);
+        }
+    }
+    Some(r)
+}
+
+/// Write RGBA chunk, VOX palette always has 256 entries
+fn write_palette_chunk(out: &mut Vec<u8>, palette: &[ColorRGBA]) {
+    let mut rgba = vec![];
+    for i in 0..256 {
+        let c = palette.get(i).copied().unwrap_or_else(ColorRGBA::empty);
+        rgba.extend_from_slice(&[c.r, c.g, c.b, c.a]);
+    }
+    write_chunk(out, b"RGBA", &rgba, &[]);
+}
+
+fn write_chunk(out: &mut Vec<u8>, id: &[u8; 4], content: &[u8], children: &[u8]) {
+    out.extend_from_slice(id);
+    write_i32(out, content.len() as i32);
+    write_i32(out, children.len() as i32);
+    out.extend_from_slice(content);
+    out.extend_from_slice(children);
+}
+
+fn write_dict(out: &mut Vec<u8>, pairs: &[(&str, &str)]) {
+    write_i32(out, pairs.len() as i32);
+    for (k, v) in pairs.iter() {
+        write_string(out, k);
+        write_string(out, v);
+    }
+}
+
+fn write_string(out: &mut Vec<u8>, s: &str) {
+    write_i32(out, s.len() as i32);
+    out.extend_from_slice(s.as_bytes());
+}
+
+#[inline]
+fn write_i32(out: &mut Vec<u8>, v: i32) {
+    out.extend_from_slice(&v.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::vox::from_vox_scene_slice;
+    use glam::Vec3;
+
+    fn assert_same_rotation(a: Quat, b: Quat) {
+        assert!(
+            Mat3::from_quat(a).abs_diff_eq(Mat3::from_quat(b), 1e-5),
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn round_trip_keeps_voxels_and_placement() {
+        let colors = [
+            ColorRGBA::new(200, 10, 10, 255),
+            ColorRGBA::new(10, 200, 10, 255),
+            ColorRGBA::new(10, 10, 200, 255),
+        ];
+        let mut model = Model::from_function(UVec3::new(3, 4, 5), |p| {
+            if (p.x + p.y + p.z) % 4 == 0 {
+                ColorRGBA::empty()
+            } else {
+                colors[(p.x + 2 * p.y + 3 * p.z) as usize % colors.len()]
+            }
+        });
+        model.offset = Vec3::new(2.0, -3.0, 7.0);
+        model.rotation = Quat::from_rotation_y(std::f32::consts::FRAC_PI_2);
+        let other = Model {
+            offset: Vec3::new(-4.0, 1.0, 0.0),
+            rotation: Quat::from_rotation_x(std::f32::consts::PI),
+            ..model.clone()
+        };
+
+        let bytes = to_vox_bytes(&[model.clone(), other.clone()]).unwrap();
+        let imported = from_vox_scene_slice(&bytes).unwrap().flatten();
+        assert_eq!(imported.len(), 2);
+        for (source, result) in [model, other].iter().zip(imported.iter()) {
+            assert_eq!(result.size, source.size);
+            assert_eq!(result.voxels, source.voxels);
+            assert!(result.offset.abs_diff_eq(source.offset, 1e-4));
+            assert_same_rotation(result.rotation, source.rotation);
+        }
+    }
+
+    #[test]
+    fn round_trip_quantizes_many_colors() {
+        let model = Model::from_function(UVec3::new(16, 16, 2), |p| {
+            ColorRGBA::new(p.x as u8 * 16, p.y as u8 * 16, p.z as u8 * 200, 255)
+        });
+        let bytes = to_vox_bytes(std::slice::from_ref(&model)).unwrap();
+        let imported = from_vox_scene_slice(&bytes).unwrap().flatten();
+        assert_eq!(imported.len(), 1);
+        let result = &imported[0];
+        assert_eq!(result.size, model.size);
+
+        let mut colors = result.voxels.clone();
+        colors.sort();
+        colors.dedup();
+        assert!(colors.len() <= VOX_PALETTE_SIZE);
+        let diff = |a: u8, b: u8| (a as i16 - b as i16).abs();
+        for (a, b) in model.voxels.iter().zip(result.voxels.iter()) {
+            assert!(!b.is_empty());
+            assert!(
+                diff(a.r, b.r) <= 16 && diff(a.g, b.g) <= 16 && diff(a.b, b.b) <= 16,
+                "{:?} became {:?}",
+                a,
+                b
+            );
+        }
+    }
+}
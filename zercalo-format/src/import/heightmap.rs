@@ -0,0 +1,182 @@
+use super::image::{from_png_file, Image, ImageImportError};
+use crate::procedure::tile::{terrain_from_heights, TerrainColoring};
+use crate::scene::Model;
+use glam::UVec3;
+
+/// Parameters of terrain generation from heightmap
+#[derive(Clone, Debug)]
+pub struct HeightmapOptions {
+    /// Height in voxels that is added to column by the white pixel
+    pub vertical_scale: f32,
+    /// Minimal height of column for opaque pixels
+    pub base_height: u32,
+    pub coloring: TerrainColoring,
+    /// Seed for random selection of weighted colors
+    pub seed: u64,
+}
+
+impl Default for HeightmapOptions {
+    fn default() -> Self {
+        HeightmapOptions {
+            vertical_scale: 32.0,
+            base_height: 1,
+            coloring: TerrainColoring::default(),
+            seed: 42,
+        }
+    }
+}
+
+/// Make terrain [`crate::scene::Model`] from grayscale heightmap. Image X axis maps to model X
+/// and image rows map to model Z. Fully transparent pixels produce no column.
+pub fn from_heightmap(
+    heightmap: &Image,
+    options: &HeightmapOptions,
+) -> Result<Model, ImageImportError> {
+    if let TerrainColoring::ColorMap(colormap) = &options.coloring {
+        if colormap.width != heightmap.width || colormap.height != heightmap.height {
+            return Err(ImageImportError::Image(format!(
+                "Color map size {}x{} differs from heightmap size {}x{}",
+                colormap.width, colormap.height, heightmap.width, heightmap.height
+            )));
+        }
+    }
+
+    let column = |x: u32, z: u32| -> u32 {
+        if heightmap.get_pixel(x, z).is_empty() {
+            0
+        } else {
+            options.base_height
+                + (heightmap.get_luminance(x, z) * options.vertical_scale).round() as u32
+        }
+    };
+    let max_height = (0..heightmap.width)
+        .flat_map(|x| (0..heightmap.height).map(move |z| (x, z)))
+        .map(|(x, z)| column(x, z))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    let rng = fastrand::Rng::with_seed(options.seed);
+    let size = UVec3::new(heightmap.width, max_height, heightmap.height);
+    Ok(terrain_from_heights(&rng, size, &options.coloring, column))
+}
+
+/// Reads a PNG heightmap from the specified path into terrain [`crate::scene::Model`]
+pub fn from_heightmap_file(
+    path: &str,
+    options: &HeightmapOptions,
+) -> Result<Model, ImageImportError> {
+    let heightmap = from_png_file(path)?;
+    from_heightmap(&heightmap, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::ColorRGBA;
+    use crate::import::image::from_png_slice;
+
+    /// Encode 8 bit grayscale with alpha PNG
+    fn grayscale_png(width: u32, height: u32, pixels: &[(u8, u8)]) -> Vec<u8> {
+        let mut out = vec![];
+        let mut encoder = png::Encoder::new(&mut out, width, height);
+        encoder.set_color(png::ColorType::GrayscaleAlpha);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        let data: Vec<u8> = pixels.iter().flat_map(|(v, a)| [*v, *a]).collect();
+        writer.write_image_data(&data).unwrap();
+        drop(writer);
+        out
+    }
+
+    fn column_height(model: &Model, x: u32, z: u32) -> u32 {
+        (0..model.size.y)
+            .filter(|y| !model.get_voxel(UVec3::new(x, *y, z)).is_empty())
+            .count() as u32
+    }
+
+    #[test]
+    fn grayscale_sets_column_heights() {
+        // Row 0: black, white, transparent. Row 1: mid grey, black, white.
+        let png = grayscale_png(
+            3,
+            2,
+            &[
+                (0, 255),
+                (255, 255),
+                (255, 0),
+                (128, 255),
+                (0, 255),
+                (255, 255),
+            ],
+        );
+        let heightmap = from_png_slice(&png).unwrap();
+        let options = HeightmapOptions {
+            vertical_scale: 4.0,
+            base_height: 1,
+            ..HeightmapOptions::default()
+        };
+        let model = from_heightmap(&heightmap, &options).unwrap();
+
+        assert_eq!(model.size, UVec3::new(3, 5, 2));
+        let heights: Vec<u32> = [(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)]
+            .iter()
+            .map(|(x, z)| column_height(&model, *x, *z))
+            .collect();
+        assert_eq!(heights, vec![1, 5, 0, 3, 1, 5]);
+        // Columns are filled from the bottom
+        assert!(!model.get_voxel(UVec3::new(1, 0, 0)).is_empty());
+        assert!(model.get_voxel(UVec3::new(0, 1, 0)).is_empty());
+    }
+
+    #[test]
+    fn color_map_fills_columns() {
+        let red = ColorRGBA::new(200, 0, 0, 255);
+        let green = ColorRGBA::new(0, 200, 0, 128);
+        let heightmap = Image {
+            width: 2,
+            height: 1,
+            pixels: vec![ColorRGBA::white(); 2],
+            luminance: vec![0.5, 1.0],
+        };
+        let colormap = Image {
+            pixels: vec![red, green],
+            ..heightmap.clone()
+        };
+        let options = HeightmapOptions {
+            vertical_scale: 2.0,
+            base_height: 0,
+            coloring: TerrainColoring::ColorMap(colormap),
+            ..HeightmapOptions::default()
+        };
+        let model = from_heightmap(&heightmap, &options).unwrap();
+
+        assert_eq!(model.size, UVec3::new(2, 2, 1));
+        assert_eq!(model.get_voxel(UVec3::new(0, 0, 0)), red);
+        assert!(model.get_voxel(UVec3::new(0, 1, 0)).is_empty());
+        for y in 0..2 {
+            assert_eq!(model.get_voxel(UVec3::new(1, y, 0)), green.with_alpha(255));
+        }
+    }
+
+    #[test]
+    fn color_map_of_other_size_fails() {
+        let heightmap = Image {
+            width: 2,
+            height: 2,
+            pixels: vec![ColorRGBA::white(); 4],
+            luminance: vec![1.0; 4],
+        };
+        let colormap = Image {
+            width: 1,
+            height: 1,
+            pixels: vec![ColorRGBA::white()],
+            luminance: vec![1.0],
+        };
+        let options = HeightmapOptions {
+            coloring: TerrainColoring::ColorMap(colormap),
+            ..HeightmapOptions::default()
+        };
+        assert!(from_heightmap(&heightmap, &options).is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use crate::color::ColorRGBA;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ImageImportError {
+    #[error("Failed to decode PNG: {0}")]
+    Png(#[from] png::DecodingError),
+    #[error("Failed to open file: {0}")]
+    File(#[from] std::io::Error),
+    #[error("Failed to import image: {0}")]
+    Image(String),
+}
+
+/// Decoded image that is used as source for voxel models
+#[derive(Clone, Debug)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    /// Pixels stored row by row from the top
+    pub pixels: Vec<ColorRGBA>,
+    /// Luminance of each pixel in range 0 .. 1. Keeps full precision of 16 bit images.
+    pub luminance: Vec<f32>,
+}
+
+impl Image {
+    /// Get pixel color, panics on boundary violation
+    pub fn get_pixel(&self, x: u32, y: u32) -> ColorRGBA {
+        self.pixels[(x + y * self.width) as usize]
+    }
+
+    /// Get pixel luminance in range 0 .. 1, panics on boundary violation
+    pub fn get_luminance(&self, x: u32, y: u32) -> f32 {
+        self.luminance[(x + y * self.width) as usize]
+    }
+}
+
+/// Reads a PNG image from a slice
+pub fn from_png_slice(slice: &[u8]) -> Result<Image, ImageImportError> {
+    from_png_reader(slice)
+}
+
+/// Reads a PNG image from the specified path
+pub fn from_png_file(path: &str) -> Result<Image, ImageImportError> {
+    let file = File::open(path)?;
+    from_png_reader(BufReader::new(file))
+}
+
+fn from_png_reader<R: Read>(r: R) -> Result<Image, ImageImportError> {
+    let mut decoder = png::Decoder::new(r);
+    // Unpack palettes and low bit depths to 8 bit samples
+    decoder.set_transformations(png::Transformations::EXPAND);
+    let (info, mut reader) = decoder.read_info()?;
+    let mut buf = vec![0; info.buffer_size()];
+    reader.next_frame(&mut buf)?;
+
+    let channels = match info.color_type {
+        png::ColorType::Grayscale => 1,
+        png::ColorType::GrayscaleAlpha => 2,
+        png::ColorType::RGB => 3,
+        png::ColorType::RGBA => 4,
+        png::ColorType::Indexed => {
+            return Err(ImageImportError::Image(
+                "Indexed colors are not expanded".to_owned(),
+            ))
+        }
+    };
+    let sample_bytes = match info.bit_depth {
+        png::BitDepth::Eight => 1,
+        png::BitDepth::Sixteen => 2,
+        other => {
+            return Err(ImageImportError::Image(format!(
+                "Unsupported bit depth {:?}",
+                other
+            )))
+        }
+    };
+    let sample = |pixel: usize, channel: usize| -> f32 {
+        let i = (pixel * channels + channel) * sample_bytes;
+        if sample_bytes == 2 {
+            u16::from_be_bytes([buf[i], buf[i + 1]]) as f32 / 65535.0
+        } else {
+            buf[i] as f32 / 255.0
+        }
+    };
+
+    let amount = (info.width * info.height) as usize;
+    let mut pixels = Vec::with_capacity(amount);
+    let mut luminance = Vec::with_capacity(amount);
+    for p in 0..amount {
+        let (r, g, b, a) = match channels {
+            1 => (sample(p, 0), sample(p, 0), sample(p, 0), 1.0),
+            2 => (sample(p, 0), sample(p, 0), sample(p, 0), sample(p, 1)),
+            3 => (sample(p, 0), sample(p, 1), sample(p, 2), 1.0),
+            _ => (sample(p, 0), sample(p, 1), sample(p, 2), sample(p, 3)),
+        };
+        let to_u8 = |v: f32| (v * 255.0).round() as u8;
+        pixels.push(ColorRGBA::new(to_u8(r), to_u8(g), to_u8(b), to_u8(a)));
+        luminance.push(0.2126 * r + 0.7152 * g + 0.0722 * b);
+    }
+
+    Ok(Image {
+        width: info.width,
+        height: info.height,
+        pixels,
+        luminance,
+    })
+}
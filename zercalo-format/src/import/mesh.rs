@@ -0,0 +1,270 @@
+use crate::color::ColorRGBA;
+use crate::scene::Model;
+use glam::{UVec3, Vec3, Vec4};
+use std::collections::VecDeque;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum MeshImportError {
+    #[error("Failed to import mesh: {0}")]
+    Mesh(String),
+    #[error("Failed to open file: {0}")]
+    File(#[from] std::io::Error),
+}
+
+#[derive(Clone, Debug)]
+pub struct Triangle {
+    pub vertices: [Vec3; 3],
+    /// Colors of vertices that are interpolated across the triangle
+    pub colors: [ColorRGBA; 3],
+}
+
+/// Triangle mesh that is loaded from OBJ or STL files and can be converted to voxels
+#[derive(Clone, Debug, Default)]
+pub struct Mesh {
+    pub triangles: Vec<Triangle>,
+}
+
+/// Parameters of mesh voxelization
+#[derive(Clone, Debug)]
+pub struct VoxelizeOptions {
+    /// Amount of voxels along the longest side of mesh bounding box
+    pub resolution: u32,
+    /// Fill interior of closed mesh, otherwise only the shell is voxelized
+    pub solid: bool,
+}
+
+impl Default for VoxelizeOptions {
+    fn default() -> Self {
+        VoxelizeOptions {
+            resolution: 64,
+            solid: true,
+        }
+    }
+}
+
+impl Mesh {
+    /// Get bounding box of all vertices
+    pub fn bounding(&self) -> (Vec3, Vec3) {
+        let mut min_vec = Vec3::splat(f32::MAX);
+        let mut max_vec = Vec3::splat(f32::MIN);
+        for v in self.triangles.iter().flat_map(|t| t.vertices.iter()) {
+            min_vec = min_vec.min(*v);
+            max_vec = max_vec.max(*v);
+        }
+        (min_vec, max_vec)
+    }
+
+    /// Swap Y and Z coordinates of vertices to convert meshes with Z axis pointing up
+    pub fn swap_yz(&mut self) {
+        for v in self
+            .triangles
+            .iter_mut()
+            .flat_map(|t| t.vertices.iter_mut())
+        {
+            *v = Vec3::new(v.x, v.z, v.y);
+        }
+    }
+
+    /// Rasterize mesh into voxel model. Mesh is scaled uniformly to fit the resolution and its
+    /// minimal corner is placed at origin of the model.
+    pub fn voxelize(&self, options: &VoxelizeOptions) -> Model {
+        if self.triangles.is_empty() {
+            return Model::new(UVec3::ONE);
+        }
+        let (min_vec, max_vec) = self.bounding();
+        let extent = (max_vec - min_vec).max_element().max(f32::EPSILON);
+        let resolution = options.resolution.max(1);
+        let scale = resolution as f32 / extent;
+        // Vertices at the far side of bounding box fall on the border of the last voxel
+        let size = ((max_vec - min_vec) * scale)
+            .ceil()
+            .as_uvec3()
+            .clamp(UVec3::ONE, UVec3::splat(resolution));
+
+        let mut model = Model::new(size);
+        for t in self.triangles.iter() {
+            let vertices = [
+                (t.vertices[0] - min_vec) * scale,
+                (t.vertices[1] - min_vec) * scale,
+                (t.vertices[2] - min_vec) * scale,
+            ];
+            rasterize_triangle(&mut model, &vertices, &t.colors);
+        }
+        if options.solid {
+            fill_interior(&mut model);
+        }
+        model
+    }
+}
+
+/// Set all voxels that intersect the triangle given in voxel coordinates
+fn rasterize_triangle(model: &mut Model, vertices: &[Vec3; 3], colors: &[ColorRGBA; 3]) {
+    let tri_min = vertices[0].min(vertices[1]).min(vertices[2]);
+    let tri_max = vertices[0].max(vertices[1]).max(vertices[2]);
+    let last = model.size.as_vec3() - Vec3::ONE;
+    let p1 = tri_min.floor().max(Vec3::ZERO).min(last).as_uvec3();
+    let p2 = tri_max.floor().max(Vec3::ZERO).min(last).as_uvec3();
+    let half = Vec3::splat(0.5);
+
+    for x in p1.x..=p2.x {
+        for y in p1.y..=p2.y {
+            for z in p1.z..=p2.z {
+                let p = UVec3::new(x, y, z);
+                let center = p.as_vec3() + half;
+                if triangle_box_overlap(center, half, vertices) {
+                    let color = interpolate_color(center, vertices, colors);
+                    model.set_voxel(p, color);
+                }
+            }
+        }
+    }
+}
+
+/// Separating axis test of triangle and axis aligned box by Tomas Akenine-Möller
+fn triangle_box_overlap(center: Vec3, half: Vec3, triangle: &[Vec3; 3]) -> bool {
+    let v = [
+        triangle[0] - center,
+        triangle[1] - center,
+        triangle[2] - center,
+    ];
+    let edges = [v[1] - v[0], v[2] - v[1], v[0] - v[2]];
+    let separated = |axis: Vec3| {
+        let p0 = axis.dot(v[0]);
+        let p1 = axis.dot(v[1]);
+        let p2 = axis.dot(v[2]);
+        let r = half.dot(axis.abs());
+        p0.min(p1).min(p2) > r || p0.max(p1).max(p2) < -r
+    };
+
+    for e in edges.iter() {
+        for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+            if separated(axis.cross(*e)) {
+                return false;
+            }
+        }
+    }
+    for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+        if separated(axis) {
+            return false;
+        }
+    }
+    !separated(edges[0].cross(edges[1]))
+}
+
+/// Interpolate vertex colors at projection of the point onto the triangle
+fn interpolate_color(p: Vec3, vertices: &[Vec3; 3], colors: &[ColorRGBA; 3]) -> ColorRGBA {
+    let e0 = vertices[1] - vertices[0];
+    let e1 = vertices[2] - vertices[0];
+    let ep = p - vertices[0];
+    let d00 = e0.dot(e0);
+    let d01 = e0.dot(e1);
+    let d11 = e1.dot(e1);
+    let d20 = ep.dot(e0);
+    let d21 = ep.dot(e1);
+    let denom = d00 * d11 - d01 * d01;
+    let weights = if denom.abs() <= f32::EPSILON {
+        Vec3::splat(1.0 / 3.0)
+    } else {
+        let v = (d11 * d20 - d01 * d21) / denom;
+        let w = (d00 * d21 - d01 * d20) / denom;
+        let clamped = Vec3::new(1.0 - v - w, v, w).max(Vec3::ZERO);
+        clamped / (clamped.x + clamped.y + clamped.z).max(f32::EPSILON)
+    };
+    let color: Vec4 = colors[0].as_vec4() * weights.x
+        + colors[1].as_vec4() * weights.y
+        + colors[2].as_vec4() * weights.z;
+    let to_u8 = |v: f32| (v * 255.0).round().clamp(0.0, 255.0) as u8;
+    ColorRGBA::new(
+        to_u8(color.x),
+        to_u8(color.y),
+        to_u8(color.z),
+        to_u8(color.w),
+    )
+}
+
+/// Fill voxels that cannot be reached from model borders without crossing the shell. Interior
+/// voxel takes color of the closest shell voxel before it along X axis.
+fn fill_interior(model: &mut Model) {
+    let size = model.size;
+    let index = |p: UVec3| (p.x + p.y * size.x + p.z * size.x * size.y) as usize;
+    let mut outside = vec![false; model.voxels.len()];
+    let mut queue = VecDeque::new();
+    for x in 0..size.x {
+        for y in 0..size.y {
+            for z in 0..size.z {
+                let border = x == 0
+                    || y == 0
+                    || z == 0
+                    || x == size.x - 1
+                    || y == size.y - 1
+                    || z == size.z - 1;
+                let p = UVec3::new(x, y, z);
+                if border && model.get_voxel(p).is_empty() {
+                    outside[index(p)] = true;
+                    queue.push_back(p);
+                }
+            }
+        }
+    }
+
+    while let Some(p) = queue.pop_front() {
+        let neighbours = [
+            (p.x > 0).then(|| p - UVec3::X),
+            (p.y > 0).then(|| p - UVec3::Y),
+            (p.z > 0).then(|| p - UVec3::Z),
+            (p.x + 1 < size.x).then(|| p + UVec3::X),
+            (p.y + 1 < size.y).then(|| p + UVec3::Y),
+            (p.z + 1 < size.z).then(|| p + UVec3::Z),
+        ];
+        for n in neighbours.into_iter().flatten() {
+            if !outside[index(n)] && model.get_voxel(n).is_empty() {
+                outside[index(n)] = true;
+                queue.push_back(n);
+            }
+        }
+    }
+
+    for z in 0..size.z {
+        for y in 0..size.y {
+            let mut shell_color = ColorRGBA::white();
+            for x in 0..size.x {
+                let p = UVec3::new(x, y, z);
+                let v = model.get_voxel(p);
+                if !v.is_empty() {
+                    shell_color = v;
+                } else if !outside[index(p)] {
+                    model.set_voxel(p, shell_color);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn longest_side_has_resolution_voxels() {
+        let white = ColorRGBA::new(255, 255, 255, 255);
+        let mesh = Mesh {
+            triangles: vec![Triangle {
+                vertices: [
+                    Vec3::ZERO,
+                    Vec3::new(4.0, 2.0, 0.0),
+                    Vec3::new(4.0, 0.0, 1.0),
+                ],
+                colors: [white; 3],
+            }],
+        };
+        let options = VoxelizeOptions {
+            resolution: 8,
+            solid: false,
+        };
+        let model = mesh.voxelize(&options);
+        assert_eq!(model.size, UVec3::new(8, 4, 2));
+        assert_eq!(model.get_voxel(UVec3::ZERO), white);
+        assert_eq!(model.get_voxel(UVec3::new(7, 0, 1)), white);
+    }
+}
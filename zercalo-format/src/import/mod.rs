@@ -1 +1,8 @@
+pub mod heightmap;
+pub mod image;
+pub mod mesh;
+pub mod obj;
+pub mod qb;
+pub mod sprite;
+pub mod stl;
 pub mod vox;
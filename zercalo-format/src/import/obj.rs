@@ -0,0 +1,154 @@
+use super::mesh::{Mesh, MeshImportError, Triangle};
+use crate::color::ColorRGBA;
+use glam::Vec3;
+use log::*;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Reads Wavefront OBJ mesh from string. Faces are colored by vertex colors (`v x y z r g b`)
+/// when present, otherwise by diffuse color of material from `materials`, otherwise white.
+pub fn from_obj_str(
+    obj: &str,
+    materials: &HashMap<String, ColorRGBA>,
+) -> Result<Mesh, MeshImportError> {
+    let mut positions: Vec<Vec3> = vec![];
+    let mut vertex_colors: Vec<Option<ColorRGBA>> = vec![];
+    let mut material_color = ColorRGBA::white();
+    let mut mesh = Mesh::default();
+
+    for (line_num, line) in obj.lines().enumerate() {
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("v") => {
+                let values = parse_floats(tokens, line_num)?;
+                if values.len() < 3 {
+                    return Err(obj_error(line_num, "vertex has less than 3 coordinates"));
+                }
+                positions.push(Vec3::new(values[0], values[1], values[2]));
+                vertex_colors.push(if values.len() >= 6 {
+                    Some(float_color(values[3], values[4], values[5], 1.0))
+                } else {
+                    None
+                });
+            }
+            Some("usemtl") => {
+                let name = tokens.next().unwrap_or_default();
+                material_color = match materials.get(name) {
+                    Some(c) => *c,
+                    None => {
+                        warn!("OBJ material {} is not found", name);
+                        ColorRGBA::white()
+                    }
+                };
+            }
+            Some("f") => {
+                let mut face = vec![];
+                for token in tokens {
+                    let index = token
+                        .split('/')
+                        .next()
+                        .and_then(|i| i.parse::<i64>().ok())
+                        .ok_or_else(|| obj_error(line_num, "invalid face index"))?;
+                    // Negative indices are relative to the end of vertex list
+                    let resolved = if index < 0 {
+                        positions.len() as i64 + index
+                    } else {
+                        index - 1
+                    };
+                    if resolved < 0 || resolved as usize >= positions.len() {
+                        return Err(obj_error(line_num, "face index out of range"));
+                    }
+                    face.push(resolved as usize);
+                }
+                let color = |i: usize| vertex_colors[i].unwrap_or(material_color);
+                // Polygons are triangulated as a fan
+                for k in 1..face.len().saturating_sub(1) {
+                    let (a, b, c) = (face[0], face[k], face[k + 1]);
+                    mesh.triangles.push(Triangle {
+                        vertices: [positions[a], positions[b], positions[c]],
+                        colors: [color(a), color(b), color(c)],
+                    });
+                }
+            }
+            _ => (),
+        }
+    }
+    Ok(mesh)
+}
+
+/// Reads diffuse colors (`Kd` and `d` or `Tr` for transparency) of materials from MTL file contents
+pub fn parse_mtl(mtl: &str) -> HashMap<String, ColorRGBA> {
+    let mut materials = HashMap::new();
+    let mut current: Option<(String, Vec3, f32)> = None;
+    for line in mtl.lines() {
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("newmtl") => {
+                if let Some((name, kd, d)) = current.take() {
+                    materials.insert(name, float_color(kd.x, kd.y, kd.z, d));
+                }
+                let name = tokens.next().unwrap_or_default().to_owned();
+                current = Some((name, Vec3::ONE, 1.0));
+            }
+            Some("Kd") => {
+                if let (Some((_, kd, _)), Ok(values)) = (current.as_mut(), parse_floats(tokens, 0))
+                {
+                    if values.len() >= 3 {
+                        *kd = Vec3::new(values[0], values[1], values[2]);
+                    }
+                }
+            }
+            Some(key @ ("d" | "Tr")) => {
+                let value = tokens.next().and_then(|v| v.parse::<f32>().ok());
+                if let (Some((_, _, d)), Some(value)) = (current.as_mut(), value) {
+                    *d = if key == "Tr" { 1.0 - value } else { value };
+                }
+            }
+            _ => (),
+        }
+    }
+    if let Some((name, kd, d)) = current {
+        materials.insert(name, float_color(kd.x, kd.y, kd.z, d));
+    }
+    materials
+}
+
+/// Reads Wavefront OBJ mesh from the specified path. Material libraries referenced by `mtllib`
+/// are loaded relative to the OBJ file.
+pub fn from_obj_file(path: &str) -> Result<Mesh, MeshImportError> {
+    let obj = fs::read_to_string(path)?;
+    let dir = Path::new(path).parent().unwrap_or_else(|| Path::new("."));
+    let mut materials = HashMap::new();
+    for line in obj.lines() {
+        let mut tokens = line.split_whitespace();
+        if tokens.next() == Some("mtllib") {
+            for lib in tokens {
+                let mtl = fs::read_to_string(dir.join(lib))?;
+                materials.extend(parse_mtl(&mtl));
+            }
+        }
+    }
+    from_obj_str(&obj, &materials)
+}
+
+fn parse_floats<'a, I: Iterator<Item = &'a str>>(
+    tokens: I,
+    line_num: usize,
+) -> Result<Vec<f32>, MeshImportError> {
+    tokens
+        .map(|t| {
+            t.parse::<f32>()
+                .map_err(|_| obj_error(line_num, "invalid number"))
+        })
+        .collect()
+}
+
+fn float_color(r: f32, g: f32, b: f32, a: f32) -> ColorRGBA {
+    let to_u8 = |v: f32| (v * 255.0).round().clamp(0.0, 255.0) as u8;
+    ColorRGBA::new(to_u8(r), to_u8(g), to_u8(b), to_u8(a))
+}
+
+fn obj_error(line_num: usize, msg: &str) -> MeshImportError {
+    MeshImportError::Mesh(format!("OBJ line {}: {}", line_num + 1, msg))
+}
@@ -0,0 +1,294 @@
+use crate::color::ColorRGBA;
+use crate::scene::Model;
+use glam::{UVec3, Vec3};
+use thiserror::Error;
+
+/// Marks RLE run in compressed matrix data, followed by count and color
+const QB_CODE_FLAG: u32 = 2;
+/// Marks end of Z slice in compressed matrix data
+const QB_NEXT_SLICE_FLAG: u32 = 6;
+/// Largest matrix that is imported. RLE runs of compressed matrix can describe any volume with a
+/// few bytes, so size from header can't be checked against the file.
+pub const QB_MAX_VOLUME: u32 = 256 * 256 * 256;
+
+#[derive(Debug, Error)]
+pub enum QbImportError {
+    #[error("Failed to import QB file: {0}")]
+    Qb(String),
+    #[error("Failed to open file: {0}")]
+    File(#[from] std::io::Error),
+}
+
+/// Reads a Qubicle binary file from a slice into [`crate::scene::Model`]. Each matrix becomes a
+/// separate model with offset taken from matrix position.
+pub fn from_qb_slice(slice: &[u8]) -> Result<Vec<Model>, QbImportError> {
+    let mut reader = QbReader {
+        data: slice,
+        pos: 0,
+    };
+    let _version = reader.u32()?;
+    let bgra = reader.u32()? == 1;
+    let right_handed = reader.u32()? == 1;
+    let compressed = reader.u32()? == 1;
+    // Visibility mask only tells which faces are visible, any non zero alpha is solid voxel
+    let _visibility_mask = reader.u32()?;
+    let matrices = reader.u32()?;
+
+    let mut models = vec![];
+    for _ in 0..matrices {
+        let name_len = reader.u8()? as usize;
+        reader.bytes(name_len)?;
+        let size = UVec3::new(reader.u32()?, reader.u32()?, reader.u32()?);
+        let pos = Vec3::new(
+            reader.i32()? as f32,
+            reader.i32()? as f32,
+            reader.i32()? as f32,
+        );
+        let volume = size
+            .x
+            .checked_mul(size.y)
+            .and_then(|v| v.checked_mul(size.z))
+            .filter(|v| *v <= QB_MAX_VOLUME)
+            .ok_or_else(|| QbImportError::Qb(format!("Matrix size {} is too large", size)))?;
+        // Each compressed slice ends with a flag
+        let min_bytes = if compressed {
+            size.z as usize * 4
+        } else {
+            volume as usize * 4
+        };
+        if min_bytes > reader.remaining() {
+            return Err(QbImportError::Qb(format!(
+                "Matrix of size {} is larger than file",
+                size
+            )));
+        }
+
+        let mut model = Model::new(size);
+        let mut set_voxel = |x: u32, y: u32, z: u32, c: u32| {
+            let z = if right_handed { size.z - z - 1 } else { z };
+            model.set_voxel(UVec3::new(x, y, z), qb_color_to_rgba(c, bgra));
+        };
+        if compressed {
+            for z in 0..size.z {
+                let mut i = 0;
+                loop {
+                    let mut data = reader.u32()?;
+                    if data == QB_NEXT_SLICE_FLAG {
+                        break;
+                    }
+                    let mut count = 1;
+                    if data == QB_CODE_FLAG {
+                        count = reader.u32()?;
+                        data = reader.u32()?;
+                    }
+                    for _ in 0..count {
+                        if i >= size.x * size.y {
+                            return Err(QbImportError::Qb(format!(
+                                "Compressed slice {} overflows matrix of size {}",
+                                z, size
+                            )));
+                        }
+                        set_voxel(i % size.x, i / size.x, z, data);
+                        i += 1;
+                    }
+                }
+            }
+        } else {
+            for z in 0..size.z {
+                for y in 0..size.y {
+                    for x in 0..size.x {
+                        let data = reader.u32()?;
+                        set_voxel(x, y, z, data);
+                    }
+                }
+            }
+        }
+
+        model.offset = if right_handed {
+            Vec3::new(pos.x, pos.y, -pos.z - size.z as f32)
+        } else {
+            pos
+        };
+        models.push(model);
+    }
+    Ok(models)
+}
+
+/// Reads a Qubicle binary file from the specified path into [`crate::scene::Model`]
+pub fn from_qb_file(path: &str) -> Result<Vec<Model>, QbImportError> {
+    let data = std::fs::read(path)?;
+    from_qb_slice(&data)
+}
+
+#[inline]
+pub fn qb_color_to_rgba(c: u32, bgra: bool) -> ColorRGBA {
+    let b0 = (c & 0xFF) as u8;
+    let b1 = ((c >> 8) & 0xFF) as u8;
+    let b2 = ((c >> 16) & 0xFF) as u8;
+    let a = ((c >> 24) & 0xFF) as u8;
+    if a == 0 {
+        ColorRGBA::empty()
+    } else if bgra {
+        ColorRGBA::new(b2, b1, b0, 255)
+    } else {
+        ColorRGBA::new(b0, b1, b2, 255)
+    }
+}
+
+/// Little endian cursor over file contents
+struct QbReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> QbReader<'a> {
+    fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], QbImportError> {
+        if self.remaining() < n {
+            return Err(QbImportError::Qb(format!(
+                "Unexpected end of file at byte {}",
+                self.pos
+            )));
+        }
+        let bytes = &self.data[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, QbImportError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, QbImportError> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn i32(&mut self) -> Result<i32, QbImportError> {
+        Ok(self.u32()? as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: u32 = 0xFF00_00FF;
+    const GREEN: u32 = 0xFF00_FF00;
+
+    fn header(compressed: bool, right_handed: bool) -> Vec<u8> {
+        let mut out = vec![];
+        for v in [0x0101_0000, 0, right_handed as u32, compressed as u32, 0, 1] {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        out
+    }
+
+    fn matrix(out: &mut Vec<u8>, size: [u32; 3], pos: [i32; 3], data: &[u32]) {
+        out.push(1);
+        out.push(b'm');
+        size.iter()
+            .for_each(|v| out.extend_from_slice(&v.to_le_bytes()));
+        pos.iter()
+            .for_each(|v| out.extend_from_slice(&v.to_le_bytes()));
+        data.iter()
+            .for_each(|v| out.extend_from_slice(&v.to_le_bytes()));
+    }
+
+    #[test]
+    fn uncompressed_matrix() {
+        let mut data = header(false, false);
+        matrix(&mut data, [2, 1, 2], [1, 2, 3], &[RED, 0, 0, GREEN]);
+        let models = from_qb_slice(&data).unwrap();
+        assert_eq!(models.len(), 1);
+        let m = &models[0];
+        assert_eq!(m.size, UVec3::new(2, 1, 2));
+        assert_eq!(m.offset, Vec3::new(1., 2., 3.));
+        assert_eq!(
+            m.get_voxel(UVec3::new(0, 0, 0)),
+            ColorRGBA::new(255, 0, 0, 255)
+        );
+        assert!(m.get_voxel(UVec3::new(1, 0, 0)).is_empty());
+        assert!(m.get_voxel(UVec3::new(0, 0, 1)).is_empty());
+        assert_eq!(
+            m.get_voxel(UVec3::new(1, 0, 1)),
+            ColorRGBA::new(0, 255, 0, 255)
+        );
+    }
+
+    #[test]
+    fn right_handed_matrix_is_mirrored_along_z() {
+        let mut data = header(false, true);
+        matrix(&mut data, [1, 1, 2], [0, 0, 4], &[RED, 0]);
+        let m = &from_qb_slice(&data).unwrap()[0];
+        assert!(m.get_voxel(UVec3::new(0, 0, 0)).is_empty());
+        assert_eq!(
+            m.get_voxel(UVec3::new(0, 0, 1)),
+            ColorRGBA::new(255, 0, 0, 255)
+        );
+        assert_eq!(m.offset, Vec3::new(0., 0., -6.));
+    }
+
+    #[test]
+    fn compressed_matrix() {
+        let mut data = header(true, false);
+        // First slice is a run of three red voxels and a green one, second is a single green
+        // voxel followed by empty ones
+        let rle = [
+            QB_CODE_FLAG,
+            3,
+            RED,
+            GREEN,
+            QB_NEXT_SLICE_FLAG,
+            GREEN,
+            QB_CODE_FLAG,
+            3,
+            0,
+            QB_NEXT_SLICE_FLAG,
+        ];
+        matrix(&mut data, [2, 2, 2], [0, 0, 0], &rle);
+        let m = &from_qb_slice(&data).unwrap()[0];
+        let red = ColorRGBA::new(255, 0, 0, 255);
+        let green = ColorRGBA::new(0, 255, 0, 255);
+        assert_eq!(m.get_voxel(UVec3::new(0, 0, 0)), red);
+        assert_eq!(m.get_voxel(UVec3::new(1, 0, 0)), red);
+        assert_eq!(m.get_voxel(UVec3::new(0, 1, 0)), red);
+        assert_eq!(m.get_voxel(UVec3::new(1, 1, 0)), green);
+        assert_eq!(m.get_voxel(UVec3::new(0, 0, 1)), green);
+        assert!(m.get_voxel(UVec3::new(1, 1, 1)).is_empty());
+    }
+
+    #[test]
+    fn compressed_slice_overflow_is_error() {
+        let mut data = header(true, false);
+        matrix(
+            &mut data,
+            [1, 1, 1],
+            [0, 0, 0],
+            &[QB_CODE_FLAG, 2, RED, QB_NEXT_SLICE_FLAG],
+        );
+        assert!(from_qb_slice(&data).is_err());
+    }
+
+    #[test]
+    fn huge_compressed_matrix_is_error() {
+        let mut data = header(true, false);
+        matrix(
+            &mut data,
+            [1600, 1600, 1600],
+            [0, 0, 0],
+            &[QB_NEXT_SLICE_FLAG],
+        );
+        assert!(from_qb_slice(&data).is_err());
+    }
+
+    #[test]
+    fn truncated_file_is_error() {
+        let mut data = header(false, false);
+        matrix(&mut data, [2, 2, 2], [0, 0, 0], &[RED]);
+        assert!(from_qb_slice(&data).is_err());
+    }
+}
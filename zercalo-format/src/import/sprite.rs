@@ -0,0 +1,184 @@
+use super::image::{from_png_file, Image, ImageImportError};
+use crate::scene::Model;
+use glam::UVec3;
+
+/// Defines how deep each pixel of sprite is extruded
+#[derive(Clone, Debug)]
+pub enum SpriteDepth {
+    /// All pixels have the same depth in voxels
+    Constant(u32),
+    /// Depth is interpolated between `min` and `max` by luminance of the pixel
+    Luminance { min: u32, max: u32 },
+    /// Depth is interpolated between `min` and `max` by luminance of the pixel of depth image that
+    /// must have the same size as the sprite
+    Map { image: Image, min: u32, max: u32 },
+}
+
+/// Parameters of sprite extrusion
+#[derive(Clone, Debug)]
+pub struct SpriteOptions {
+    pub depth: SpriteDepth,
+    /// Extrude pixels symmetrically from the middle plane instead of from the back plane
+    pub centered: bool,
+    /// Pixels with alpha less or equal to the value are not extruded
+    pub alpha_threshold: u8,
+}
+
+impl Default for SpriteOptions {
+    fn default() -> Self {
+        SpriteOptions {
+            depth: SpriteDepth::Constant(1),
+            centered: false,
+            alpha_threshold: 0,
+        }
+    }
+}
+
+/// Make [`crate::scene::Model`] by extruding non transparent pixels of the sprite along Z axis.
+/// Image X axis maps to model X and the top row of image becomes the top of model.
+pub fn from_sprite(sprite: &Image, options: &SpriteOptions) -> Result<Model, ImageImportError> {
+    let (min_depth, max_depth) = match &options.depth {
+        SpriteDepth::Constant(depth) => (*depth, *depth),
+        SpriteDepth::Luminance { min, max } => (*min, *max),
+        SpriteDepth::Map { image, min, max } => {
+            if image.width != sprite.width || image.height != sprite.height {
+                return Err(ImageImportError::Image(format!(
+                    "Depth map size {}x{} differs from sprite size {}x{}",
+                    image.width, image.height, sprite.width, sprite.height
+                )));
+            }
+            (*min, *max)
+        }
+    };
+    let depth_at = |x: u32, y: u32| -> u32 {
+        let luminance = match &options.depth {
+            SpriteDepth::Constant(_) => 0.0,
+            SpriteDepth::Luminance { .. } => sprite.get_luminance(x, y),
+            SpriteDepth::Map { image, .. } => image.get_luminance(x, y),
+        };
+        min_depth + (luminance * max_depth.saturating_sub(min_depth) as f32).round() as u32
+    };
+
+    let size = UVec3::new(sprite.width, sprite.height, max_depth.max(min_depth).max(1));
+    let mut model = Model::new(size);
+    for x in 0..sprite.width {
+        for y in 0..sprite.height {
+            let color = sprite.get_pixel(x, y);
+            if color.a <= options.alpha_threshold {
+                continue;
+            }
+            let depth = depth_at(x, y).min(size.z);
+            let start = if options.centered {
+                (size.z - depth) / 2
+            } else {
+                0
+            };
+            for z in start..start + depth {
+                model.set_voxel(UVec3::new(x, sprite.height - y - 1, z), color);
+            }
+        }
+    }
+    Ok(model)
+}
+
+/// Reads a PNG sprite from the specified path and extrudes it into [`crate::scene::Model`]
+pub fn from_sprite_file(path: &str, options: &SpriteOptions) -> Result<Model, ImageImportError> {
+    let sprite = from_png_file(path)?;
+    from_sprite(&sprite, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::ColorRGBA;
+    use crate::import::image::from_png_slice;
+
+    const RED: ColorRGBA = ColorRGBA::new(255, 0, 0, 255);
+    const BLUE: ColorRGBA = ColorRGBA::new(0, 0, 255, 255);
+
+    /// 2x2 RGBA sprite: red and transparent at the top, blue and white at the bottom
+    fn sprite() -> Image {
+        let pixels = [RED, ColorRGBA::empty(), BLUE, ColorRGBA::white()];
+        let mut out = vec![];
+        let mut encoder = png::Encoder::new(&mut out, 2, 2);
+        encoder.set_color(png::ColorType::RGBA);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        let data: Vec<u8> = pixels.iter().flat_map(|c| [c.r, c.g, c.b, c.a]).collect();
+        writer.write_image_data(&data).unwrap();
+        drop(writer);
+        from_png_slice(&out).unwrap()
+    }
+
+    fn depth_of(model: &Model, x: u32, y: u32) -> u32 {
+        (0..model.size.z)
+            .filter(|z| !model.get_voxel(UVec3::new(x, y, *z)).is_empty())
+            .count() as u32
+    }
+
+    #[test]
+    fn constant_depth_keeps_orientation() {
+        let options = SpriteOptions {
+            depth: SpriteDepth::Constant(3),
+            ..SpriteOptions::default()
+        };
+        let model = from_sprite(&sprite(), &options).unwrap();
+        assert_eq!(model.size, UVec3::new(2, 2, 3));
+        // The top row of image is the top of model
+        for z in 0..3 {
+            assert_eq!(model.get_voxel(UVec3::new(0, 1, z)), RED);
+            assert_eq!(model.get_voxel(UVec3::new(0, 0, z)), BLUE);
+            assert_eq!(model.get_voxel(UVec3::new(1, 0, z)), ColorRGBA::white());
+        }
+        assert_eq!(depth_of(&model, 1, 1), 0);
+    }
+
+    #[test]
+    fn luminance_depth() {
+        let options = SpriteOptions {
+            depth: SpriteDepth::Luminance { min: 1, max: 5 },
+            ..SpriteOptions::default()
+        };
+        let model = from_sprite(&sprite(), &options).unwrap();
+        assert_eq!(model.size.z, 5);
+        assert_eq!(depth_of(&model, 1, 0), 5);
+        assert_eq!(depth_of(&model, 0, 1), 2);
+        assert_eq!(depth_of(&model, 0, 0), 1);
+        assert_eq!(depth_of(&model, 1, 1), 0);
+        // Not centered extrusion starts at the back plane
+        assert!(!model.get_voxel(UVec3::new(0, 0, 0)).is_empty());
+    }
+
+    #[test]
+    fn centered_extrusion() {
+        let options = SpriteOptions {
+            depth: SpriteDepth::Luminance { min: 1, max: 5 },
+            centered: true,
+            ..SpriteOptions::default()
+        };
+        let model = from_sprite(&sprite(), &options).unwrap();
+        let solid: Vec<u32> = (0..5)
+            .filter(|z| !model.get_voxel(UVec3::new(0, 0, *z)).is_empty())
+            .collect();
+        assert_eq!(solid, vec![2]);
+    }
+
+    #[test]
+    fn depth_map_of_other_size_fails() {
+        let image = sprite();
+        let options = SpriteOptions {
+            depth: SpriteDepth::Map {
+                image: Image {
+                    width: 1,
+                    height: 1,
+                    pixels: vec![ColorRGBA::white()],
+                    luminance: vec![1.0],
+                },
+                min: 0,
+                max: 2,
+            },
+            ..SpriteOptions::default()
+        };
+        assert!(from_sprite(&image, &options).is_err());
+    }
+}
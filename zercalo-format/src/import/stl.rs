@@ -0,0 +1,114 @@
+use super::mesh::{Mesh, MeshImportError, Triangle};
+use crate::color::ColorRGBA;
+use glam::Vec3;
+
+/// Size of binary STL header before amount of triangles
+const STL_HEADER_SIZE: usize = 80;
+/// Size of each triangle record in binary STL
+const STL_TRIANGLE_SIZE: usize = 50;
+
+/// Reads binary or ASCII STL mesh from a slice. Binary files can store per face color in
+/// attribute bytes (VisCAM and SolidView convention), otherwise faces are white. STL files
+/// usually point Z axis up, use [`Mesh::swap_yz`] to convert them.
+pub fn from_stl_slice(slice: &[u8]) -> Result<Mesh, MeshImportError> {
+    let binary_size = slice
+        .get(STL_HEADER_SIZE..STL_HEADER_SIZE + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+        .map(|n| STL_HEADER_SIZE + 4 + n * STL_TRIANGLE_SIZE);
+    // Some binary files also start with "solid", so size check goes first
+    if binary_size == Some(slice.len()) || !slice.starts_with(b"solid") {
+        from_stl_binary(slice)
+    } else {
+        let text = std::str::from_utf8(slice)
+            .map_err(|e| MeshImportError::Mesh(format!("STL is not valid text: {}", e)))?;
+        from_stl_ascii(text)
+    }
+}
+
+/// Reads binary or ASCII STL mesh from the specified path
+pub fn from_stl_file(path: &str) -> Result<Mesh, MeshImportError> {
+    let data = std::fs::read(path)?;
+    from_stl_slice(&data)
+}
+
+fn from_stl_binary(slice: &[u8]) -> Result<Mesh, MeshImportError> {
+    let amount = slice
+        .get(STL_HEADER_SIZE..STL_HEADER_SIZE + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+        .ok_or_else(|| MeshImportError::Mesh("STL header is truncated".to_owned()))?;
+    let body = &slice[STL_HEADER_SIZE + 4..];
+    if body.len() < amount * STL_TRIANGLE_SIZE {
+        return Err(MeshImportError::Mesh(format!(
+            "STL declares {} triangles, but has data for {}",
+            amount,
+            body.len() / STL_TRIANGLE_SIZE
+        )));
+    }
+
+    let f32_at = |b: &[u8], i: usize| f32::from_le_bytes([b[i], b[i + 1], b[i + 2], b[i + 3]]);
+    let vec_at = |b: &[u8], i: usize| Vec3::new(f32_at(b, i), f32_at(b, i + 4), f32_at(b, i + 8));
+    let mut mesh = Mesh::default();
+    for record in body.chunks_exact(STL_TRIANGLE_SIZE).take(amount) {
+        // Normal at the start of record is skipped as it can be restored from vertices
+        let attribute = u16::from_le_bytes([record[48], record[49]]);
+        let color = stl_color_to_rgba(attribute);
+        mesh.triangles.push(Triangle {
+            vertices: [vec_at(record, 12), vec_at(record, 24), vec_at(record, 36)],
+            colors: [color; 3],
+        });
+    }
+    Ok(mesh)
+}
+
+fn from_stl_ascii(text: &str) -> Result<Mesh, MeshImportError> {
+    let mut mesh = Mesh::default();
+    let mut vertices = vec![];
+    for (line_num, line) in text.lines().enumerate() {
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("vertex") => {
+                let coords: Result<Vec<f32>, _> = tokens.map(|t| t.parse::<f32>()).collect();
+                match coords {
+                    Ok(c) if c.len() == 3 => vertices.push(Vec3::new(c[0], c[1], c[2])),
+                    _ => {
+                        return Err(MeshImportError::Mesh(format!(
+                            "STL line {}: invalid vertex",
+                            line_num + 1
+                        )))
+                    }
+                }
+            }
+            Some("endfacet") => {
+                if vertices.len() == 3 {
+                    mesh.triangles.push(Triangle {
+                        vertices: [vertices[0], vertices[1], vertices[2]],
+                        colors: [ColorRGBA::white(); 3],
+                    });
+                } else {
+                    return Err(MeshImportError::Mesh(format!(
+                        "STL line {}: facet has {} vertices",
+                        line_num + 1,
+                        vertices.len()
+                    )));
+                }
+                vertices.clear();
+            }
+            _ => (),
+        }
+    }
+    Ok(mesh)
+}
+
+/// Decode 15 bit color from attribute bytes. Bit 15 tells that color is valid, blue is stored in
+/// bits 0-4, green in 5-9 and red in 10-14.
+#[inline]
+pub fn stl_color_to_rgba(attribute: u16) -> ColorRGBA {
+    if attribute & 0x8000 == 0 {
+        return ColorRGBA::white();
+    }
+    let channel = |shift: u16| {
+        let v = ((attribute >> shift) & 0x1F) as u8;
+        (v << 3) | (v >> 2)
+    };
+    ColorRGBA::new(channel(10), channel(5), channel(0), 255)
+}
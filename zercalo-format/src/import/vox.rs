@@ -1,8 +1,12 @@
+use crate::animation::Switcher;
 use crate::color::ColorRGBA;
-use crate::scene::Model;
+use crate::scene::{IndexedModel, Model, Scene, INDEXED_PALETTE_SIZE};
+use dot_vox::SceneNode;
 use glam::f32::Quat;
-use glam::{UVec3, Vec3};
-use std::collections::HashMap;
+use glam::{IVec3, Mat3, UVec3, Vec3};
+use log::*;
+use std::fs;
+use std::path::Path;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -11,6 +15,8 @@ pub enum VoxImportError {
     Vox(String),
     #[error("Failed to open file: {0}")]
     File(#[from] std::io::Error),
+    #[error("No files match the sequence pattern: {0}")]
+    EmptySequence(String),
 }
 
 /// Reads a VOX file from a slice into [`crate::scene::Model`]
@@ -39,28 +45,657 @@ pub fn from_vox_data(data: dot_vox::DotVoxData) -> Vec<Model> {
 }
 
 /// Import parsed VOX model with given pallete to own model format
-pub fn from_vox_model(pallete: &[u32], vox_model: &dot_vox::Model) -> Model {
+pub fn from_vox_model(pallete: &[dot_vox::Color], vox_model: &dot_vox::Model) -> Model {
     let size = UVec3::new(vox_model.size.x, vox_model.size.z, vox_model.size.y);
     let mut voxels = vec![ColorRGBA::empty(); (size.x * size.y * size.z) as usize];
     for v in vox_model.voxels.iter() {
         let i = v.x as u32 + v.z as u32 * size.x + v.y as u32 * size.x * size.y;
-        voxels[i as usize] = vox_color_to_rgba(pallete[v.i as usize]);
+        voxels[i as usize] = vox_color_to_rgba(&pallete[v.i as usize]);
     }
     Model {
         size,
         voxels,
         offset: Vec3::new(0.0, 0.0, 0.0),
         rotation: Quat::from_axis_angle(Vec3::Y, 0.0),
-        replace_colors: HashMap::new(),
+        ..Model::default()
     }
 }
 
+/// Reads a VOX file from a slice into [`crate::scene::IndexedModel`] that keeps palette of file
+pub fn from_vox_slice_indexed(slice: &[u8]) -> Result<Vec<IndexedModel>, VoxImportError> {
+    let voxdata = dot_vox::load_bytes(slice).map_err(|e| VoxImportError::Vox(e.to_owned()))?;
+    Ok(from_vox_data_indexed(&voxdata))
+}
+
+/// Reads a VOX file from the specified path into [`crate::scene::IndexedModel`]
+pub fn from_vox_file_indexed(path: &str) -> Result<Vec<IndexedModel>, VoxImportError> {
+    let voxdata = dot_vox::load(path).map_err(|e| VoxImportError::Vox(e.to_owned()))?;
+    Ok(from_vox_data_indexed(&voxdata))
+}
+
+/// Reads a VOX file from parsed in memory data into [`crate::scene::IndexedModel`]
+pub fn from_vox_data_indexed(data: &dot_vox::DotVoxData) -> Vec<IndexedModel> {
+    data.models
+        .iter()
+        .map(|m| from_vox_model_indexed(&data.palette, m))
+        .collect()
+}
+
+/// Import parsed VOX model keeping its palette. Index 0 of VOX palette is reserved for empty
+/// voxels, so palette entry `i` of the file becomes index `i + 1` of the model.
+pub fn from_vox_model_indexed(
+    pallete: &[dot_vox::Color],
+    vox_model: &dot_vox::Model,
+) -> IndexedModel {
+    let size = UVec3::new(vox_model.size.x, vox_model.size.z, vox_model.size.y);
+    let mut palette = vec![ColorRGBA::empty()];
+    palette.extend(
+        pallete
+            .iter()
+            .take(INDEXED_PALETTE_SIZE - 1)
+            .map(vox_color_to_rgba),
+    );
+    let mut model = IndexedModel {
+        palette,
+        ..IndexedModel::new(size)
+    };
+    for v in vox_model.voxels.iter() {
+        let i = v.x as u32 + v.z as u32 * size.x + v.y as u32 * size.x * size.y;
+        model.indices[i as usize] = v.i.saturating_add(1);
+    }
+    model
+}
+
+#[inline]
+pub fn vox_color_to_rgba(c: &dot_vox::Color) -> ColorRGBA {
+    ColorRGBA::new(c.r, c.g, c.b, c.a)
+}
+
+/// Node of MagicaVoxel scene graph. Each transform node of VOX file becomes a [`VoxNode`] that
+/// holds either children (from group node) or models (from shape node). Several models of a shape
+/// node are frames of its animation.
+#[derive(Clone, Debug)]
+pub struct VoxNode {
+    /// Value of `_name` attribute of transform node
+    pub name: Option<String>,
+    /// Value of `_hidden` attribute of transform node or its layer
+    pub hidden: bool,
+    /// Translation relative to parent node
+    pub position: Vec3,
+    /// Rotation relative to parent node
+    pub rotation: Quat,
+    /// Models of shape node with offsets relative to this node
+    pub models: Vec<Model>,
+    /// Animation frame of MagicaVoxel for each model, sorted ascending
+    pub keyframes: Vec<u32>,
+    pub children: Vec<VoxNode>,
+}
+
+impl Default for VoxNode {
+    fn default() -> Self {
+        VoxNode {
+            name: None,
+            hidden: false,
+            position: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            models: vec![],
+            keyframes: vec![],
+            children: vec![],
+        }
+    }
+}
+
+impl VoxNode {
+    /// Find first node with given name in the subtree
+    pub fn find(&self, name: &str) -> Option<&VoxNode> {
+        if self.name.as_deref() == Some(name) {
+            return Some(self);
+        }
+        self.children.iter().find_map(|c| c.find(name))
+    }
+
+    /// Check that shape of the node has more than one animation frame
+    pub fn is_animated(&self) -> bool {
+        self.models.len() > 1
+    }
+
+    /// Get model of the shape that is shown at given MagicaVoxel animation frame
+    pub fn model_at(&self, frame: u32) -> Option<&Model> {
+        let i = self
+            .keyframes
+            .iter()
+            .rposition(|k| *k <= frame)
+            .unwrap_or(0);
+        self.models.get(i)
+    }
+
+    /// Collect all visible models of subtree with offsets and rotations in world space
+    pub fn flatten(&self) -> Vec<Model> {
+        self.flatten_at(0)
+    }
+
+    /// Collect all visible models of subtree in world space at given MagicaVoxel animation frame
+    pub fn flatten_at(&self, frame: u32) -> Vec<Model> {
+        let mut models = vec![];
+        self.walk(
+            Vec3::ZERO,
+            Quat::IDENTITY,
+            &mut |node, position, rotation| {
+                if let Some(m) = node.model_at(frame) {
+                    models.push(place_model(m, position, rotation));
+                }
+            },
+        );
+        models
+    }
+
+    /// Convert each visible shape of subtree to switcher of models in world space. Each frame of
+    /// MagicaVoxel animation lasts `frame_duration` frames and the last keyframe is shown for
+    /// `frame_duration` frames. Shapes without animation give switchers with single variant.
+    pub fn switchers(&self, frame_duration: u32) -> Vec<Switcher<Model>> {
+        let mut switchers = vec![];
+        self.walk(
+            Vec3::ZERO,
+            Quat::IDENTITY,
+            &mut |node, position, rotation| {
+                if node.models.is_empty() {
+                    return;
+                }
+                let mut frames = vec![];
+                for (i, m) in node.models.iter().enumerate() {
+                    let duration = match node.keyframes.get(i + 1) {
+                        Some(next) => (next - node.keyframes[i]) * frame_duration,
+                        None => frame_duration,
+                    };
+                    frames.push((duration, place_model(m, position, rotation)));
+                }
+                switchers.push(Switcher::new(frames));
+            },
+        );
+        switchers
+    }
+
+    /// Visit all visible nodes of subtree with their world position and rotation
+    fn walk<F>(&self, parent_pos: Vec3, parent_rot: Quat, visitor: &mut F)
+    where
+        F: FnMut(&VoxNode, Vec3, Quat),
+    {
+        if self.hidden {
+            return;
+        }
+        let rotation = parent_rot * self.rotation;
+        let position = parent_pos + parent_rot.mul_vec3(self.position);
+        visitor(self, position, rotation);
+        for c in self.children.iter() {
+            c.walk(position, rotation, visitor);
+        }
+    }
+
+    /// Make scene with default camera and lights from all visible models
+    pub fn to_scene(&self) -> Scene {
+        Scene {
+            models: self.flatten(),
+            ..Scene::default()
+        }
+    }
+}
+
+/// Move model from node local space to world space
+fn place_model(m: &Model, position: Vec3, rotation: Quat) -> Model {
+    let mut model = m.clone();
+    // Renderer applies model offset before rotation
+    model.rotation = rotation * m.rotation;
+    model.offset = m.offset + model.rotation.inverse().mul_vec3(position);
+    model
+}
+
+/// Reads a VOX file from a slice with its scene graph
+pub fn from_vox_scene_slice(slice: &[u8]) -> Result<VoxNode, VoxImportError> {
+    let voxdata = dot_vox::load_bytes(slice).map_err(|e| VoxImportError::Vox(e.to_owned()))?;
+    Ok(from_vox_scene(&voxdata))
+}
+
+/// Reads a VOX file from the specified path with its scene graph
+pub fn from_vox_scene_file(path: &str) -> Result<VoxNode, VoxImportError> {
+    let voxdata = dot_vox::load(path).map_err(|e| VoxImportError::Vox(e.to_owned()))?;
+    Ok(from_vox_scene(&voxdata))
+}
+
+/// Import scene graph of parsed VOX file. Unlike [`from_vox_data`] models are positioned as they
+/// are placed in MagicaVoxel editor. Files without scene graph produce node with child per model.
+pub fn from_vox_scene(data: &dot_vox::DotVoxData) -> VoxNode {
+    if data.scenes.is_empty() {
+        return VoxNode {
+            children: data
+                .models
+                .iter()
+                .map(|m| VoxNode {
+                    models: vec![from_vox_model(&data.palette, m)],
+                    keyframes: vec![0],
+                    ..VoxNode::default()
+                })
+                .collect(),
+            ..VoxNode::default()
+        };
+    }
+    from_vox_transform(data, 0, false, 0).unwrap_or_default()
+}
+
+/// Reads animated shapes of VOX file into switchers of models, see [`VoxNode::switchers`]
+pub fn from_vox_animation_file(
+    path: &str,
+    frame_duration: u32,
+) -> Result<Vec<Switcher<Model>>, VoxImportError> {
+    Ok(from_vox_scene_file(path)?.switchers(frame_duration))
+}
+
+/// Reads numbered sequence of VOX files into switcher where each file is a frame lasting
+/// `frame_duration` frames. Pattern is a path with single `*` in file name that stands for frame
+/// number, e.g. `./assets/models/harvester/harvester_track_*.vox`. Only the first model of each
+/// file is taken and files are ordered by the number.
+pub fn from_vox_sequence(
+    pattern: &str,
+    frame_duration: u32,
+) -> Result<Switcher<Model>, VoxImportError> {
+    let path = Path::new(pattern);
+    let dir = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    let file_pattern = path
+        .file_name()
+        .and_then(|f| f.to_str())
+        .unwrap_or_default();
+    let (prefix, suffix) = file_pattern.split_once('*').unwrap_or((file_pattern, ""));
+
+    let mut files = vec![];
+    for entry in fs::read_dir(dir)? {
+        let file_path = entry?.path();
+        let name = match file_path.file_name().and_then(|f| f.to_str()) {
+            Some(name) => name,
+            None => continue,
+        };
+        let number = name
+            .strip_prefix(prefix)
+            .and_then(|n| n.strip_suffix(suffix))
+            .and_then(|n| n.parse::<u32>().ok());
+        if let Some(number) = number {
+            files.push((number, file_path));
+        }
+    }
+    if files.is_empty() {
+        return Err(VoxImportError::EmptySequence(pattern.to_owned()));
+    }
+    files.sort();
+
+    let mut frames = vec![];
+    for (_, file_path) in files.into_iter() {
+        let file_path = file_path.to_string_lossy();
+        debug!("Loading frame {} of sequence {}", file_path, pattern);
+        let model = from_vox_file(&file_path)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| VoxImportError::Vox(format!("Zero models in {}", file_path)))?;
+        frames.push((frame_duration, model));
+    }
+    Ok(Switcher::new(frames))
+}
+
+/// Rotation matrix stored by rows in VOX axes. MagicaVoxel allows mirroring in it.
+type VoxRotation = [[i32; 3]; 3];
+
+const VOX_IDENTITY: VoxRotation = [[1, 0, 0], [0, 1, 0], [0, 0, 1]];
+const VOX_FLIP_X: VoxRotation = [[-1, 0, 0], [0, 1, 0], [0, 0, 1]];
+
+/// Import transform node. Quaternions cannot express mirroring, so reflection of `_r` attribute is
+/// factored out as flip along X axis and pushed down to children until it is baked into voxels of
+/// shape models. `flip` tells that parent passed such reflection.
+fn from_vox_transform(
+    data: &dot_vox::DotVoxData,
+    node_id: u32,
+    flip: bool,
+    depth: usize,
+) -> Option<VoxNode> {
+    if depth > data.scenes.len() {
+        warn!("VOX scene graph has a cycle at node {}", node_id);
+        return None;
+    }
+    let (attributes, frames, child, layer_id) = match data.scenes.get(node_id as usize) {
+        Some(SceneNode::Transform {
+            attributes,
+            frames,
+            child,
+            layer_id,
+        }) => (attributes, frames, child, layer_id),
+        _ => {
+            warn!("VOX scene node {} is not a transform node", node_id);
+            return None;
+        }
+    };
+
+    let frame = frames.first().map(|f| &f.attributes);
+    let mut translation = frame
+        .and_then(|f| f.get("_t"))
+        .map(|t| parse_vox_translation(t))
+        .unwrap_or(IVec3::ZERO);
+    let mut rotation = frame
+        .and_then(|f| f.get("_r"))
+        .and_then(|r| r.parse::<u8>().ok())
+        .map(parse_vox_rotation)
+        .unwrap_or(VOX_IDENTITY);
+    if flip {
+        rotation = rot_mul(&VOX_FLIP_X, &rotation);
+        translation = rot_mul_vec(&VOX_FLIP_X, translation);
+    }
+    let child_flip = rot_det(&rotation) < 0;
+    if child_flip {
+        rotation = rot_mul(&rotation, &VOX_FLIP_X);
+    }
+
+    let layer_hidden = data
+        .layers
+        .get(*layer_id as usize)
+        .map(|l| l.hidden())
+        .unwrap_or(false);
+    let mut node = VoxNode {
+        name: attributes.get("_name").cloned(),
+        hidden: layer_hidden || attributes.get("_hidden").map(|h| h == "1").unwrap_or(false),
+        position: swap_vox_axes(translation).as_vec3(),
+        rotation: vox_rotation_to_quat(&rotation),
+        ..VoxNode::default()
+    };
+    match data.scenes.get(*child as usize) {
+        Some(SceneNode::Group { children, .. }) => {
+            node.children = children
+                .iter()
+                .filter_map(|c| from_vox_transform(data, *c, child_flip, depth + 1))
+                .collect();
+        }
+        Some(SceneNode::Shape { models, .. }) => {
+            let mut frames = vec![];
+            for (i, shape_model) in models.iter().enumerate() {
+                let keyframe = shape_model
+                    .attributes
+                    .get("_f")
+                    .and_then(|f| f.parse::<u32>().ok())
+                    .unwrap_or(i as u32);
+                match data.models.get(shape_model.model_id as usize) {
+                    Some(m) => {
+                        frames.push((keyframe, from_vox_shape_model(&data.palette, m, child_flip)))
+                    }
+                    None => warn!("VOX shape refers to missing model {}", shape_model.model_id),
+                }
+            }
+            frames.sort_by_key(|(k, _)| *k);
+            let (keyframes, models) = frames.into_iter().unzip();
+            node.keyframes = keyframes;
+            node.models = models;
+        }
+        _ => warn!("VOX transform node {} has invalid child {}", node_id, child),
+    }
+    Some(node)
+}
+
+/// Import model of shape node. MagicaVoxel places the model by its center, so offset is set to
+/// move the center to node origin.
+fn from_vox_shape_model(
+    pallete: &[dot_vox::Color],
+    vox_model: &dot_vox::Model,
+    flip: bool,
+) -> Model {
+    let mut model = from_vox_model(pallete, vox_model);
+    let size = IVec3::new(
+        vox_model.size.x as i32,
+        vox_model.size.y as i32,
+        vox_model.size.z as i32,
+    );
+    let pivot = size / 2;
+    let offset = if flip {
+        let size = model.size;
+        let mut flipped = Model::new(size);
+        for x in 0..size.x {
+            for y in 0..size.y {
+                for z in 0..size.z {
+                    let v = model.get_voxel(UVec3::new(x, y, z));
+                    flipped.set_voxel(UVec3::new(size.x - x - 1, y, z), v);
+                }
+            }
+        }
+        model.voxels = flipped.voxels;
+        IVec3::new(pivot.x - size.x as i32, -pivot.y, -pivot.z)
+    } else {
+        -pivot
+    };
+    model.offset = swap_vox_axes(offset).as_vec3();
+    model
+}
+
+/// Parse `_t` attribute of transform frame
+fn parse_vox_translation(t: &str) -> IVec3 {
+    let mut components = t.split_whitespace().map(|c| c.parse::<i32>().unwrap_or(0));
+    let x = components.next().unwrap_or(0);
+    let y = components.next().unwrap_or(0);
+    let z = components.next().unwrap_or(0);
+    IVec3::new(x, y, z)
+}
+
+/// Parse `_r` attribute of transform frame. Bits 0-1 and 2-3 are indices of non zero entries in
+/// the first and second rows, bits 4-6 are signs of the rows.
+fn parse_vox_rotation(r: u8) -> VoxRotation {
+    let first = ((r & 0b11) as usize).min(2);
+    let second = (((r >> 2) & 0b11) as usize).min(2);
+    let third = (0..3).find(|i| *i != first && *i != second).unwrap_or(2);
+    let mut m = [[0; 3]; 3];
+    for (row, col) in [first, second, third].into_iter().enumerate() {
+        let sign = if r & (1 << (4 + row)) != 0 { -1 } else { 1 };
+        m[row][col] = sign;
+    }
+    m
+}
+
+fn rot_mul(a: &VoxRotation, b: &VoxRotation) -> VoxRotation {
+    let mut m = [[0; 3]; 3];
+    for (i, row) in m.iter_mut().enumerate() {
+        for (j, v) in row.iter_mut().enumerate() {
+            *v = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    m
+}
+
+fn rot_mul_vec(a: &VoxRotation, v: IVec3) -> IVec3 {
+    let row = |i: usize| a[i][0] * v.x + a[i][1] * v.y + a[i][2] * v.z;
+    IVec3::new(row(0), row(1), row(2))
+}
+
+fn rot_det(a: &VoxRotation) -> i32 {
+    a[0][0] * (a[1][1] * a[2][2] - a[1][2] * a[2][1])
+        - a[0][1] * (a[1][0] * a[2][2] - a[1][2] * a[2][0])
+        + a[0][2] * (a[1][0] * a[2][1] - a[1][1] * a[2][0])
+}
+
+/// Convert proper rotation from VOX axes to our axes where Y and Z are swapped
+fn vox_rotation_to_quat(a: &VoxRotation) -> Quat {
+    let swap = |i: usize| [0, 2, 1][i];
+    let col = |j: usize| {
+        Vec3::new(
+            a[swap(0)][swap(j)] as f32,
+            a[swap(1)][swap(j)] as f32,
+            a[swap(2)][swap(j)] as f32,
+        )
+    };
+    Quat::from_mat3(&Mat3::from_cols(col(0), col(1), col(2)))
+}
+
 #[inline]
-pub fn vox_color_to_rgba(c: u32) -> ColorRGBA {
-    ColorRGBA::new(
-        (c & 0xFF) as u8,
-        ((c >> 8) & 0xFF) as u8,
-        ((c >> 16) & 0xFF) as u8,
-        ((c >> 24) & 0xFF) as u8,
-    )
+fn swap_vox_axes(v: IVec3) -> IVec3 {
+    IVec3::new(v.x, v.z, v.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_i32(out: &mut Vec<u8>, v: i32) {
+        out.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn write_dict(out: &mut Vec<u8>, pairs: &[(&str, &str)]) {
+        write_i32(out, pairs.len() as i32);
+        for s in pairs.iter().flat_map(|(k, v)| [k, v]) {
+            write_i32(out, s.len() as i32);
+            out.extend_from_slice(s.as_bytes());
+        }
+    }
+
+    fn write_chunk(out: &mut Vec<u8>, id: &[u8; 4], content: &[u8]) {
+        out.extend_from_slice(id);
+        write_i32(out, content.len() as i32);
+        write_i32(out, 0);
+        out.extend_from_slice(content);
+    }
+
+    fn model_chunks(out: &mut Vec<u8>, size: [i32; 3]) {
+        let mut content = vec![];
+        size.iter().for_each(|v| write_i32(&mut content, *v));
+        write_chunk(out, b"SIZE", &content);
+        let mut content = vec![];
+        write_i32(&mut content, 1);
+        content.extend_from_slice(&[0, 0, 0, 1]);
+        write_chunk(out, b"XYZI", &content);
+    }
+
+    fn transform(out: &mut Vec<u8>, id: i32, attrs: &[(&str, &str)], child: i32, layer: i32) {
+        let frame: Vec<_> = attrs
+            .iter()
+            .filter(|(k, _)| k.starts_with("_t") || k.starts_with("_r"))
+            .copied()
+            .collect();
+        let attrs: Vec<_> = attrs
+            .iter()
+            .filter(|(k, _)| !k.starts_with("_t") && !k.starts_with("_r"))
+            .copied()
+            .collect();
+        let mut content = vec![];
+        write_i32(&mut content, id);
+        write_dict(&mut content, &attrs);
+        write_i32(&mut content, child);
+        write_i32(&mut content, -1);
+        write_i32(&mut content, layer);
+        write_i32(&mut content, 1);
+        write_dict(&mut content, &frame);
+        write_chunk(out, b"nTRN", &content);
+    }
+
+    fn shape(out: &mut Vec<u8>, id: i32, model: i32) {
+        let mut content = vec![];
+        write_i32(&mut content, id);
+        write_dict(&mut content, &[]);
+        write_i32(&mut content, 1);
+        write_i32(&mut content, model);
+        write_dict(&mut content, &[]);
+        write_chunk(out, b"nSHP", &content);
+    }
+
+    fn layer(out: &mut Vec<u8>, id: i32, attrs: &[(&str, &str)]) {
+        let mut content = vec![];
+        write_i32(&mut content, id);
+        write_dict(&mut content, attrs);
+        write_i32(&mut content, -1);
+        write_chunk(out, b"LAYR", &content);
+    }
+
+    /// Group with translated shape, shape rotated by 90 degrees around VOX Z axis and shape on
+    /// hidden layer
+    fn scene_fixture() -> Vec<u8> {
+        let mut children = vec![];
+        model_chunks(&mut children, [2, 4, 6]);
+        model_chunks(&mut children, [2, 2, 2]);
+        transform(&mut children, 0, &[], 1, -1);
+        let mut group = vec![];
+        write_i32(&mut group, 1);
+        write_dict(&mut group, &[]);
+        write_i32(&mut group, 3);
+        [2, 4, 6].iter().for_each(|c| write_i32(&mut group, *c));
+        write_chunk(&mut children, b"nGRP", &group);
+        transform(
+            &mut children,
+            2,
+            &[("_name", "moved"), ("_t", "10 20 30")],
+            3,
+            0,
+        );
+        shape(&mut children, 3, 0);
+        transform(
+            &mut children,
+            4,
+            &[("_name", "rotated"), ("_t", "0 0 5"), ("_r", "17")],
+            5,
+            0,
+        );
+        shape(&mut children, 5, 1);
+        transform(&mut children, 6, &[("_name", "on_hidden_layer")], 7, 1);
+        shape(&mut children, 7, 0);
+        layer(&mut children, 0, &[]);
+        layer(&mut children, 1, &[("_hidden", "1")]);
+
+        let mut out = b"VOX ".to_vec();
+        write_i32(&mut out, 150);
+        out.extend_from_slice(b"MAIN");
+        write_i32(&mut out, 0);
+        write_i32(&mut out, children.len() as i32);
+        out.extend_from_slice(&children);
+        out
+    }
+
+    fn assert_near(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-4, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn scene_graph_is_assembled() {
+        let root = from_vox_scene_slice(&scene_fixture()).unwrap();
+
+        let moved = root.find("moved").unwrap();
+        assert_near(moved.position, Vec3::new(10., 30., 20.));
+        assert_eq!(moved.models[0].size, UVec3::new(2, 6, 4));
+        // Model center is placed at node origin
+        assert_near(moved.models[0].offset, Vec3::new(-1., -3., -2.));
+
+        let rotated = root.find("rotated").unwrap();
+        // VOX X goes to VOX Y, that is our Z
+        assert_near(rotated.rotation.mul_vec3(Vec3::X), Vec3::Z);
+        assert_near(rotated.rotation.mul_vec3(Vec3::Y), Vec3::Y);
+
+        let hidden = root.find("on_hidden_layer").unwrap();
+        assert!(hidden.hidden);
+    }
+
+    #[test]
+    fn flattened_models_are_in_world_space() {
+        let models = from_vox_scene_slice(&scene_fixture()).unwrap().flatten();
+        assert_eq!(models.len(), 2);
+        let world = |m: &Model, p: Vec3| m.rotation.mul_vec3(p + m.offset);
+
+        assert_near(world(&models[0], Vec3::ZERO), Vec3::new(9., 27., 18.));
+        // Corner (-1, -1, -1) relative to center is rotated to (1, -1, -1) and moved up by 5
+        assert_near(world(&models[1], Vec3::ZERO), Vec3::new(1., 4., -1.));
+    }
+
+    #[test]
+    fn file_without_scene_graph_gives_node_per_model() {
+        let mut children = vec![];
+        model_chunks(&mut children, [1, 1, 1]);
+        model_chunks(&mut children, [1, 1, 1]);
+        let mut data = b"VOX ".to_vec();
+        write_i32(&mut data, 150);
+        data.extend_from_slice(b"MAIN");
+        write_i32(&mut data, 0);
+        write_i32(&mut data, children.len() as i32);
+        data.extend_from_slice(&children);
+
+        let root = from_vox_scene_slice(&data).unwrap();
+        assert_eq!(root.children.len(), 2);
+        assert_eq!(root.flatten().len(), 2);
+    }
 }
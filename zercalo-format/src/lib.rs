@@ -1,5 +1,6 @@
 pub mod animation;
 pub mod color;
+pub mod export;
 pub mod import;
 pub mod procedure;
 pub mod scene;
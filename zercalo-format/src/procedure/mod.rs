@@ -1,3 +1,4 @@
 pub mod particles;
 pub mod smoke;
 pub mod tile;
+pub mod transition;
@@ -2,8 +2,9 @@ use glam::f32::Quat;
 use glam::{UVec3, Vec3};
 use std::ops::Bound;
 
-use crate::color::ColorRGBA;
-use crate::scene::{HasBounding, Model};
+use crate::animation::{Animatable, HasCycle};
+use crate::color::{ColorRGBA, ColorRamp};
+use crate::scene::{Aabb, HasBounding, Model, Obb};
 
 #[derive(Clone, Debug)]
 pub struct Particle {
@@ -11,6 +12,22 @@ pub struct Particle {
     pub vel: Vec3,
     pub col: ColorRGBA,
     pub size: u8,
+    /// Amount of animation steps the particle lived at frame 0
+    pub age: u32,
+}
+
+impl Particle {
+    /// State of the particle after given amount of animation steps. Each step moves particle by
+    /// its velocity and then accelerates it by gravity.
+    pub fn at(&self, frame: u32, gravity: Vec3) -> Particle {
+        let n = frame as f32;
+        Particle {
+            pos: self.pos + self.vel * n + gravity * (n * (n - 1.0) * 0.5),
+            vel: self.vel + gravity * n,
+            age: self.age + frame,
+            ..self.clone()
+        }
+    }
 }
 
 impl Default for Particle {
@@ -20,6 +37,7 @@ impl Default for Particle {
             vel: Vec3::ZERO,
             col: ColorRGBA::empty(),
             size: 1,
+            age: 0,
         }
     }
 }
@@ -29,8 +47,15 @@ pub struct ParticlesModel {
     pub size: UVec3,
     pub offset: Vec3,
     pub rotation: Quat,
+    /// Particles at frame 0
     pub particles: Vec<Particle>,
     pub gravity: Vec3,
+    /// When set, particles are colored by their age relative to `lifetime` instead of own color
+    pub color_over_life: Option<ColorRamp>,
+    /// Amount of animation steps that maps to the end of `color_over_life`
+    pub lifetime: u32,
+    /// Frame the model was evaluated at
+    pub frame: u32,
 }
 
 impl Default for ParticlesModel {
@@ -41,16 +66,16 @@ impl Default for ParticlesModel {
             rotation: Quat::from_axis_angle(Vec3::Y, 0.0),
             particles: vec![],
             gravity: Vec3::ZERO,
+            color_over_life: None,
+            lifetime: 1,
+            frame: 0,
         }
     }
 }
 
 impl HasBounding for ParticlesModel {
-    fn get_bounding_volume(&self) -> (Vec3, Vec3) {
-        (
-            self.offset,
-            self.offset + self.rotation.mul_vec3(self.size.as_vec3()),
-        )
+    fn get_bounding_volume(&self) -> Aabb {
+        Obb::from_volume(self.size.as_vec3(), self.offset, self.rotation).aabb()
     }
 }
 
@@ -93,6 +118,7 @@ impl ParticlesModel {
                 vel: random_vec3(rng, vel_range),
                 size: rng.u8(default_range(size_range)),
                 col: colors_pool[rng.usize(default_range((0, colors_pool.len())))],
+                age: 0,
             };
         }
 
@@ -103,11 +129,16 @@ impl ParticlesModel {
         }
     }
 
-    /// Render particles into voxel volume
+    /// Render particles at the evaluated frame into voxel volume
     pub fn generate(&self) -> Model {
         let mut model = Model::new(self.size);
 
         for part in self.particles.iter() {
+            let part = part.at(self.frame, self.gravity);
+            let color = match &self.color_over_life {
+                Some(ramp) => ramp.sample(part.age as f32 / self.lifetime.max(1) as f32),
+                None => part.col,
+            };
             let p1 = part.pos.max(Vec3::ZERO).as_uvec3();
             let p2 = (part.pos + part.size as f32)
                 .min(model.size.as_vec3())
@@ -116,7 +147,7 @@ impl ParticlesModel {
             for x in p1.x..p2.x {
                 for y in p1.y..p2.y {
                     for z in p1.z..p2.z {
-                        model.set_voxel(UVec3::new(x, y, z), part.col);
+                        model.set_voxel(UVec3::new(x, y, z), color);
                     }
                 }
             }
@@ -126,12 +157,17 @@ impl ParticlesModel {
         model.offset = self.offset;
         model
     }
+}
 
-    /// Step animation of particles
-    pub fn animate(&mut self, _frame: u32) {
-        for part in self.particles.iter_mut() {
-            part.pos += part.vel;
-            part.vel += self.gravity;
-        }
+impl Animatable for ParticlesModel {
+    fn evaluate(&mut self, frame: u32) {
+        self.frame = frame;
+    }
+}
+
+/// Particles fall and die, so they never repeat
+impl HasCycle for ParticlesModel {
+    fn cycle_frames(&self) -> Option<u32> {
+        None
     }
 }
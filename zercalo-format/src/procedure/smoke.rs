@@ -2,9 +2,11 @@ use glam::f32::Quat;
 use glam::{UVec3, Vec3};
 use noise::{NoiseFn, OpenSimplex};
 
-use crate::color::ColorRGBA;
-use crate::scene::{HasBounding, Model};
+use crate::animation::{Animatable, HasCycle};
+use crate::color::{ColorRGBA, ColorRamp, RampInterpolation};
+use crate::scene::{Aabb, HasBounding, Model, Obb};
 
+#[derive(Clone, Debug)]
 pub struct SmokePart {
     pub offset: Vec3,
     pub radius: f32,
@@ -20,13 +22,17 @@ pub struct SmokeModel {
     pub size: UVec3,
     pub offset: Vec3,
     pub rotation: Quat,
+    /// Particles at frame 0
     pub particles: Vec<SmokePart>,
     pub noise: OpenSimplex,
-    pub cold_color: ColorRGBA,
-    pub hot_color: ColorRGBA,
-    pub very_hot_color: ColorRGBA,
+    /// Colors of smoke by distance from particle center relative to radius of its hot core
+    /// (`temperature * radius`), 0 is the center and 1 or more is the cold edge. Particles above
+    /// ceiling take color at 1.
+    pub heat_colors: ColorRamp,
     pub ceiling_height: f32,
     pub ceiling_speed: f32, // how fast parts shrinks after ceiling
+    /// Frame the model was evaluated at
+    pub frame: u32,
 }
 
 impl Default for SmokeModel {
@@ -37,21 +43,47 @@ impl Default for SmokeModel {
             rotation: Quat::from_axis_angle(Vec3::Y, 0.0),
             particles: vec![],
             noise: OpenSimplex::new(),
-            cold_color: ColorRGBA::new(111, 123, 155, 255),
-            hot_color: ColorRGBA::new(229, 88, 41, 255),
-            very_hot_color: ColorRGBA::new(249, 195, 0, 255),
+            heat_colors: default_heat_colors(),
             ceiling_height: 42.0,
             ceiling_speed: -0.1,
+            frame: 0,
         }
     }
 }
 
+/// Three colors of very hot core, hot middle and cold edge of smoke
+pub fn default_heat_colors() -> ColorRamp {
+    ColorRamp::from_stops(
+        RampInterpolation::Step,
+        &[
+            (0.0, ColorRGBA::new(249, 195, 0, 255)),
+            (0.7, ColorRGBA::new(229, 88, 41, 255)),
+            (1.0, ColorRGBA::new(111, 123, 155, 255)),
+        ],
+    )
+}
+
+impl SmokePart {
+    /// State of the part after given amount of animation steps. Parts grow until they rise above
+    /// the ceiling and shrink after that.
+    pub fn at(&self, frame: u32, ceiling_height: f32, ceiling_speed: f32) -> SmokePart {
+        let mut part = self.clone();
+        for _ in 0..frame {
+            part.offset += part.velocity;
+            if part.offset.y > ceiling_height {
+                part.radius = (part.radius + ceiling_speed).max(0.0);
+            } else {
+                part.radius += part.radius_vel;
+            }
+            part.temperature += part.temperature_speed;
+        }
+        part
+    }
+}
+
 impl HasBounding for SmokeModel {
-    fn get_bounding_volume(&self) -> (Vec3, Vec3) {
-        (
-            self.offset,
-            self.offset + self.rotation.mul_vec3(self.size.as_vec3()),
-        )
+    fn get_bounding_volume(&self) -> Aabb {
+        Obb::from_volume(self.size.as_vec3(), self.offset, self.rotation).aabb()
     }
 }
 
@@ -61,10 +93,15 @@ impl SmokeModel {
         SmokeModel::default()
     }
 
-    /// Convert smoke to model
+    /// Convert smoke at the evaluated frame to model
     pub fn generate(&self) -> Model {
+        let particles: Vec<SmokePart> = self
+            .particles
+            .iter()
+            .map(|p| p.at(self.frame, self.ceiling_height, self.ceiling_speed))
+            .collect();
         let mut model = Model::from_function(self.size, |pos| {
-            for part in self.particles.iter() {
+            for part in particles.iter() {
                 let d2 = (part.offset - pos.as_vec3()).length_squared();
 
                 let dr = (self.noise.get([
@@ -76,16 +113,10 @@ impl SmokeModel {
                 let dr2 = d2 + dr;
                 if dr2 < part.radius * part.radius && dr2 > 0.0 && part.radius > 0.0 {
                     let cold_radius = part.temperature * part.radius;
-                    let very_hot_radius = cold_radius * 0.7;
-                    if part.offset.y > self.ceiling_height {
-                        return self.cold_color;
-                    } else if dr2 < very_hot_radius * very_hot_radius {
-                        return self.very_hot_color;
-                    } else if dr2 < cold_radius * cold_radius {
-                        return self.hot_color;
-                    } else {
-                        return self.cold_color;
+                    if part.offset.y > self.ceiling_height || cold_radius <= 0.0 {
+                        return self.heat_colors.sample(1.0);
                     }
+                    return self.heat_colors.sample(dr2.sqrt() / cold_radius);
                 }
             }
             ColorRGBA::empty()
@@ -94,20 +125,17 @@ impl SmokeModel {
         model.offset = self.offset;
         model
     }
+}
 
-    /// Step animation of smoke
-    pub fn animate(&mut self, _frame: u32) {
-        for part in self.particles.iter_mut() {
-            part.offset += part.velocity;
-            if part.offset.y > self.ceiling_height {
-                part.radius += self.ceiling_speed;
-                if part.radius < 0.0 {
-                    part.radius = 0.0;
-                }
-            } else {
-                part.radius += part.radius_vel;
-            }
-            part.temperature += part.temperature_speed;
-        }
+impl Animatable for SmokeModel {
+    fn evaluate(&mut self, frame: u32) {
+        self.frame = frame;
+    }
+}
+
+/// Smoke parts move and die, so smoke never repeats
+impl HasCycle for SmokeModel {
+    fn cycle_frames(&self) -> Option<u32> {
+        None
     }
 }
@@ -0,0 +1,80 @@
+use glam::UVec3;
+
+use crate::color::{ColorRGBA, ColorRamp};
+use crate::import::image::Image;
+use crate::scene::Model;
+
+/// Defines how voxels of terrain columns are colored
+#[derive(Clone, Debug)]
+pub enum TerrainColoring {
+    /// Each voxel gets random color from the list with given weights
+    Weighted(Vec<(ColorRGBA, f32)>),
+    /// Voxels are colored by their height. Each band is upper bound of height relative to the
+    /// model height in range 0 .. 1 and weighted colors for voxels below it.
+    Bands(Vec<(f32, Vec<(ColorRGBA, f32)>)>),
+    /// Voxels are colored by the ramp at their height relative to the model height in range
+    /// 0 .. 1
+    Ramp(ColorRamp),
+    /// Whole column takes color of the pixel, image must have the same size as the tile base
+    ColorMap(Image),
+}
+
+impl Default for TerrainColoring {
+    fn default() -> Self {
+        TerrainColoring::Weighted(vec![(ColorRGBA::white(), 1.0)])
+    }
+}
+
+/// Select value depending on the assigned weight
+pub fn weighted<'a, T>(rng: &fastrand::Rng, values: &'a [(T, f32)]) -> &'a T {
+    assert!(!values.is_empty(), "Empty weighted list");
+    let total_weight: f32 = values.iter().map(|(_, w)| w).sum();
+    let selected = rng.f32() * total_weight;
+    let mut accum = 0.0;
+    for (v, w) in values {
+        if selected < accum + w {
+            return v;
+        }
+        accum += w;
+    }
+    &values[0].0
+}
+
+/// Generate terrain by filling voxel columns from the ground. Function `heights` gives height of
+/// column at X and Z coordinates, heights are clamped by the model size.
+pub fn terrain_from_heights<F>(
+    rng: &fastrand::Rng,
+    size: UVec3,
+    coloring: &TerrainColoring,
+    heights: F,
+) -> Model
+where
+    F: Fn(u32, u32) -> u32,
+{
+    let mut model = Model::new(size);
+    for x in 0..size.x {
+        for z in 0..size.z {
+            let height = heights(x, z).min(size.y);
+            for y in 0..height {
+                let color = match coloring {
+                    TerrainColoring::Weighted(colors) => *weighted(rng, colors),
+                    TerrainColoring::Bands(bands) => {
+                        let relative = y as f32 / size.y as f32;
+                        let band = bands
+                            .iter()
+                            .find(|(upper, _)| relative < *upper)
+                            .or_else(|| bands.last());
+                        match band {
+                            Some((_, colors)) if !colors.is_empty() => *weighted(rng, colors),
+                            _ => ColorRGBA::white(),
+                        }
+                    }
+                    TerrainColoring::Ramp(ramp) => ramp.sample(y as f32 / size.y as f32),
+                    TerrainColoring::ColorMap(image) => image.get_pixel(x, z).with_alpha(255),
+                };
+                model.set_voxel(UVec3::new(x, y, z), color);
+            }
+        }
+    }
+    model
+}
@@ -0,0 +1,325 @@
+use glam::{UVec3, Vec3};
+use noise::{NoiseFn, OpenSimplex};
+
+use super::particles::Particle;
+use crate::animation::{Animatable, HasCycle, HasMarkers, Marker};
+use crate::color::ColorRGBA;
+use crate::scene::{Aabb, HasBounding, HasModels, Model, Obb};
+use std::ops::Range;
+
+/// Noise values are mostly in range -0.3 .. 0.3, they are stretched to cover the whole transition
+const NOISE_STRETCH: f32 = 1.6;
+
+/// How voxels of [`TransitionModel`] change from source to target
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransitionStyle {
+    /// Each voxel switches at its own time that is given by noise, `scale` is the size of noise
+    /// features in voxels
+    Dissolve { scale: f32 },
+    /// Voxels switch layer by layer from the bottom to the top
+    BuildUp,
+    /// Voxels of source break off in random order and fly away as particles by their `speed`
+    /// (voxels per frame) and `gravity`, while voxels of target appear in their place
+    Scatter { speed: f32, gravity: Vec3 },
+}
+
+/// Procedural transition between two models, like construction, teleport or death of a unit.
+/// Both models are in the same local space, voxels with the same coordinates are matched. Use
+/// [`TransitionModel::appear`] and [`TransitionModel::vanish`] for transitions from and to
+/// nothing.
+pub struct TransitionModel {
+    /// Model at frame 0
+    pub from: Model,
+    /// Model at the end of transition
+    pub to: Model,
+    pub style: TransitionStyle,
+    /// Duration of transition in frames
+    pub frames: u32,
+    /// Empty voxels around the models, so scattered particles can fly out of them
+    pub padding: u32,
+    pub noise: OpenSimplex,
+    /// Seed for random order and velocities of scattered voxels
+    pub seed: u64,
+    /// Frame the model was evaluated at
+    pub frame: u32,
+}
+
+impl TransitionModel {
+    /// Transition between models, the result is placed as `from` model
+    pub fn new(from: Model, to: Model, style: TransitionStyle, frames: u32) -> Self {
+        TransitionModel {
+            from,
+            to,
+            style,
+            frames,
+            padding: 0,
+            noise: OpenSimplex::new(),
+            seed: 42,
+            frame: 0,
+        }
+    }
+
+    /// Model appears from nothing
+    pub fn appear(to: Model, style: TransitionStyle, frames: u32) -> Self {
+        let from = Model {
+            offset: to.offset,
+            rotation: to.rotation,
+            ..Model::new(to.size)
+        };
+        TransitionModel::new(from, to, style, frames)
+    }
+
+    /// Model disappears
+    pub fn vanish(from: Model, style: TransitionStyle, frames: u32) -> Self {
+        let to = Model::new(from.size);
+        TransitionModel::new(from, to, style, frames)
+    }
+
+    pub fn with_padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Size of voxel grid of both models
+    fn content_size(&self) -> UVec3 {
+        self.from.size.max(self.to.size)
+    }
+
+    /// Size of generated model including padding
+    pub fn size(&self) -> UVec3 {
+        self.content_size() + UVec3::splat(2 * self.padding)
+    }
+
+    /// Progress of transition in range 0 .. 1 at the evaluated frame
+    pub fn progress(&self) -> f32 {
+        if self.frames == 0 {
+            1.0
+        } else {
+            (self.frame as f32 / self.frames as f32).min(1.0)
+        }
+    }
+
+    fn rng(&self, p: UVec3) -> fastrand::Rng {
+        let size = self.content_size();
+        let i = p.x as u64 + p.y as u64 * size.x as u64 + p.z as u64 * (size.x * size.y) as u64;
+        fastrand::Rng::with_seed(self.seed ^ i.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+    }
+
+    /// Progress at which the voxel switches from source to target, in range 0 .. 1
+    pub fn threshold(&self, p: UVec3) -> f32 {
+        let t = match self.style {
+            TransitionStyle::Dissolve { scale } => {
+                let s = 1.0 / scale.max(f32::EPSILON) as f64;
+                let n = self
+                    .noise
+                    .get([p.x as f64 * s, p.y as f64 * s, p.z as f64 * s]);
+                n as f32 * NOISE_STRETCH + 0.5
+            }
+            TransitionStyle::BuildUp => (p.y as f32 + 0.5) / self.content_size().y as f32,
+            TransitionStyle::Scatter { .. } => self.rng(p).f32(),
+        };
+        t.clamp(0.0, 1.0)
+    }
+
+    /// Whether the voxel already shows the target
+    fn switched(&self, p: UVec3, progress: f32) -> bool {
+        progress >= 1.0 || self.threshold(p) < progress
+    }
+
+    /// Particle that the voxel of source turns into when it breaks off
+    fn scattered(&self, p: UVec3, color: ColorRGBA, speed: f32) -> Particle {
+        let rng = self.rng(p);
+        // The first value is taken by threshold
+        rng.f32();
+        let center = self.content_size().as_vec3() * 0.5;
+        let pos = p.as_vec3() + Vec3::splat(0.5);
+        let outward = (pos - center).try_normalize().unwrap_or(Vec3::Y);
+        let jitter = Vec3::new(rng.f32(), rng.f32(), rng.f32()) - Vec3::splat(0.5);
+        Particle {
+            pos: p.as_vec3() + Vec3::splat(self.padding as f32),
+            vel: (outward + jitter) * speed,
+            col: color,
+            size: 1,
+            age: 0,
+        }
+    }
+
+    /// Generate intermediate model at the evaluated frame
+    pub fn generate(&self) -> Model {
+        let progress = self.progress();
+        let padding = UVec3::splat(self.padding);
+        let voxel = |model: &Model, p: UVec3| {
+            if p.cmplt(model.size).all() {
+                model.get_rendered_voxel(p)
+            } else {
+                ColorRGBA::empty()
+            }
+        };
+        let mut model = Model::from_function(self.size(), |pos| {
+            if pos.cmplt(padding).any() {
+                return ColorRGBA::empty();
+            }
+            let p = pos - padding;
+            if self.switched(p, progress) {
+                voxel(&self.to, p)
+            } else {
+                voxel(&self.from, p)
+            }
+        });
+
+        if let TransitionStyle::Scatter { speed, gravity } = self.style {
+            let size = self.from.size;
+            for z in 0..size.z {
+                for y in 0..size.y {
+                    for x in 0..size.x {
+                        let p = UVec3::new(x, y, z);
+                        let color = self.from.get_rendered_voxel(p);
+                        if color.is_empty() || !self.switched(p, progress) {
+                            continue;
+                        }
+                        let release = (self.threshold(p) * self.frames as f32).floor() as u32 + 1;
+                        let age = self.frame.saturating_sub(release);
+                        let part = self.scattered(p, color, speed).at(age, gravity);
+                        let pos = part.pos.round();
+                        if pos.cmpge(Vec3::ZERO).all() && pos.cmplt(model.size.as_vec3()).all() {
+                            model.set_voxel(pos.as_uvec3(), part.col);
+                        }
+                    }
+                }
+            }
+        }
+
+        model.rotation = self.from.rotation;
+        model.offset = self.from.offset - padding.as_vec3();
+        model
+    }
+}
+
+impl HasBounding for TransitionModel {
+    fn get_bounding_volume(&self) -> Aabb {
+        let offset = self.from.offset - Vec3::splat(self.padding as f32);
+        Obb::from_volume(self.size().as_vec3(), offset, self.from.rotation).aabb()
+    }
+}
+
+/// Model is generated on each call, so it can be a [`crate::animation::Part`] of composition
+impl HasModels for TransitionModel {
+    fn get_models(&self) -> Vec<Model> {
+        vec![self.generate()]
+    }
+}
+
+impl HasMarkers for TransitionModel {
+    fn get_markers(&self, _frames: Range<u32>) -> Vec<Marker> {
+        vec![]
+    }
+}
+
+impl Animatable for TransitionModel {
+    fn evaluate(&mut self, frame: u32) {
+        self.frame = frame;
+    }
+}
+
+/// Transition plays once and scattered particles keep flying after it
+impl HasCycle for TransitionModel {
+    fn cycle_frames(&self) -> Option<u32> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAMES: u32 = 8;
+    const SOURCE_COLOR: ColorRGBA = ColorRGBA::new(200, 40, 40, 255);
+    const TARGET_COLOR: ColorRGBA = ColorRGBA::new(40, 40, 200, 255);
+
+    fn solid(color: ColorRGBA) -> Model {
+        let mut model = Model::from_function(UVec3::splat(4), |_| color);
+        model.offset = Vec3::new(1.0, 2.0, 3.0);
+        model
+    }
+
+    /// Amounts of voxels with colors of source and target inside the content region
+    fn content_colors(transition: &mut TransitionModel, frame: u32) -> (usize, usize) {
+        transition.evaluate(frame);
+        let models = transition.get_models();
+        assert_eq!(models.len(), 1);
+        let model = &models[0];
+        assert_eq!(model.size, transition.size());
+        let padding = UVec3::splat(transition.padding);
+        let mut counts = (0, 0);
+        for z in 0..4 {
+            for y in 0..4 {
+                for x in 0..4 {
+                    let color = model.get_voxel(UVec3::new(x, y, z) + padding);
+                    if color == SOURCE_COLOR {
+                        counts.0 += 1;
+                    } else if color == TARGET_COLOR {
+                        counts.1 += 1;
+                    }
+                }
+            }
+        }
+        counts
+    }
+
+    fn filled(model: &Model) -> usize {
+        model.voxels.iter().filter(|c| !c.is_empty()).count()
+    }
+
+    fn transition(style: TransitionStyle) -> TransitionModel {
+        TransitionModel::new(solid(SOURCE_COLOR), solid(TARGET_COLOR), style, FRAMES)
+    }
+
+    #[test]
+    fn dissolve() {
+        let mut transition = transition(TransitionStyle::Dissolve { scale: 1.5 });
+        assert_eq!(content_colors(&mut transition, 0), (64, 0));
+        let (from, to) = content_colors(&mut transition, FRAMES / 2);
+        assert!(from > 0 && to > 0 && from + to == 64);
+        assert_eq!(content_colors(&mut transition, FRAMES), (0, 64));
+        assert_eq!(transition.generate().offset, Vec3::new(1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn build_up() {
+        let mut transition = transition(TransitionStyle::BuildUp);
+        assert_eq!(content_colors(&mut transition, 0), (64, 0));
+        // Two bottom layers of four are switched
+        assert_eq!(content_colors(&mut transition, FRAMES / 2), (32, 32));
+        let model = transition.generate();
+        assert_eq!(model.get_voxel(UVec3::new(0, 1, 0)), TARGET_COLOR);
+        assert_eq!(model.get_voxel(UVec3::new(0, 2, 0)), SOURCE_COLOR);
+        assert_eq!(content_colors(&mut transition, FRAMES), (0, 64));
+    }
+
+    #[test]
+    fn scatter() {
+        let mut transition = transition(TransitionStyle::Scatter {
+            speed: 1.0,
+            gravity: Vec3::ZERO,
+        })
+        .with_padding(8);
+        assert_eq!(content_colors(&mut transition, 0), (64, 0));
+        let model = transition.generate();
+        assert_eq!(model.offset, Vec3::new(1.0, 2.0, 3.0) - Vec3::splat(8.0));
+        assert_eq!(filled(&model), 64);
+        // Released voxels are replaced by target and fly away as particles
+        let (from, to) = content_colors(&mut transition, FRAMES / 2);
+        assert!(from > 0 && to > 0);
+        assert!(filled(&transition.generate()) > 64);
+        let (from, to) = content_colors(&mut transition, FRAMES);
+        assert!(to > from);
+        assert!(filled(&transition.generate()) > 64);
+        // Later all particles are out of the target
+        assert_eq!(content_colors(&mut transition, 3 * FRAMES), (0, 64));
+    }
+}
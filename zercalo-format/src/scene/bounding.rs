@@ -0,0 +1,222 @@
+use glam::f32::Quat;
+use glam::Vec3;
+
+/// Axis aligned bounding box in world space
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Default for Aabb {
+    fn default() -> Self {
+        Aabb::empty()
+    }
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Aabb { min, max }
+    }
+
+    /// Box that contains nothing, union with it doesn't change other box
+    pub fn empty() -> Self {
+        Aabb {
+            min: Vec3::splat(f32::MAX),
+            max: Vec3::splat(f32::MIN),
+        }
+    }
+
+    /// Smallest box that contains all points
+    pub fn from_points<I: IntoIterator<Item = Vec3>>(points: I) -> Self {
+        points.into_iter().fold(Aabb::empty(), |b, p| Aabb {
+            min: b.min.min(p),
+            max: b.max.max(p),
+        })
+    }
+
+    /// Check that box has no volume and contains no points
+    pub fn is_empty(&self) -> bool {
+        self.min.cmpgt(self.max).any()
+    }
+
+    /// Middle point of the box, origin for empty box
+    pub fn center(&self) -> Vec3 {
+        if self.is_empty() {
+            Vec3::ZERO
+        } else {
+            (self.min + self.max) * 0.5
+        }
+    }
+
+    /// Lengths of box sides, zero for empty box
+    pub fn size(&self) -> Vec3 {
+        (self.max - self.min).max(Vec3::ZERO)
+    }
+
+    /// Check that point is inside of the box or on its border
+    pub fn contains(&self, p: Vec3) -> bool {
+        p.cmpge(self.min).all() && p.cmple(self.max).all()
+    }
+
+    /// Smallest box that contains both boxes
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    /// Common part of both boxes, `None` if they don't overlap
+    pub fn intersection(&self, other: &Aabb) -> Option<Aabb> {
+        let common = Aabb {
+            min: self.min.max(other.min),
+            max: self.max.min(other.max),
+        };
+        if common.is_empty() {
+            None
+        } else {
+            Some(common)
+        }
+    }
+
+    /// Corners of the box
+    pub fn corners(&self) -> [Vec3; 8] {
+        let (a, b) = (self.min, self.max);
+        [
+            Vec3::new(a.x, a.y, a.z),
+            Vec3::new(b.x, a.y, a.z),
+            Vec3::new(a.x, b.y, a.z),
+            Vec3::new(b.x, b.y, a.z),
+            Vec3::new(a.x, a.y, b.z),
+            Vec3::new(b.x, a.y, b.z),
+            Vec3::new(a.x, b.y, b.z),
+            Vec3::new(b.x, b.y, b.z),
+        ]
+    }
+
+    /// Axis aligned box that contains this box after rotation around origin
+    pub fn rotated(&self, rotation: Quat) -> Aabb {
+        if self.is_empty() {
+            return *self;
+        }
+        Aabb::from_points(self.corners().iter().map(|c| rotation.mul_vec3(*c)))
+    }
+}
+
+/// Oriented bounding box, box with given half sizes rotated around its center
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Obb {
+    pub center: Vec3,
+    pub half_size: Vec3,
+    pub rotation: Quat,
+}
+
+impl Obb {
+    /// Box of voxel volume with given size that is placed as renderer does with models, that is
+    /// volume is moved by `offset` and then rotated around world origin.
+    pub fn from_volume(size: Vec3, offset: Vec3, rotation: Quat) -> Self {
+        let half_size = size * 0.5;
+        Obb {
+            center: rotation.mul_vec3(offset + half_size),
+            half_size,
+            rotation,
+        }
+    }
+
+    /// Corners of the box in world space
+    pub fn corners(&self) -> [Vec3; 8] {
+        let local = Aabb::new(-self.half_size, self.half_size);
+        let mut corners = local.corners();
+        for c in corners.iter_mut() {
+            *c = self.center + self.rotation.mul_vec3(*c);
+        }
+        corners
+    }
+
+    /// Axis aligned box that contains the oriented box
+    pub fn aabb(&self) -> Aabb {
+        Aabb::from_points(self.corners())
+    }
+
+    /// Check that point is inside of the box or on its border
+    pub fn contains(&self, p: Vec3) -> bool {
+        let local = self.rotation.inverse().mul_vec3(p - self.center);
+        local.abs().cmple(self.half_size).all()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::Model;
+    use glam::UVec3;
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, SQRT_2};
+
+    fn assert_aabb(b: Aabb, min: Vec3, max: Vec3) {
+        assert!(
+            b.min.abs_diff_eq(min, 1e-5) && b.max.abs_diff_eq(max, 1e-5),
+            "{:?} != {:?} .. {:?}",
+            b,
+            min,
+            max
+        );
+    }
+
+    #[test]
+    fn quarter_turn_volume() {
+        let rotation = Quat::from_rotation_y(FRAC_PI_2);
+        let obb = Obb::from_volume(Vec3::new(4.0, 2.0, 6.0), Vec3::X, rotation);
+        // Volume spans x 1 .. 5 and z 0 .. 6, turn maps x to -z and z to x
+        assert_aabb(
+            obb.aabb(),
+            Vec3::new(0.0, 0.0, -5.0),
+            Vec3::new(6.0, 2.0, -1.0),
+        );
+        assert!(obb.contains(Vec3::new(3.0, 1.0, -3.0)));
+        assert!(!obb.contains(Vec3::new(3.0, 1.0, 3.0)));
+
+        let model = Model {
+            offset: Vec3::X,
+            rotation,
+            ..Model::new(UVec3::new(4, 2, 6))
+        };
+        assert_aabb(model.aabb(), obb.aabb().min, obb.aabb().max);
+    }
+
+    #[test]
+    fn diagonal_turn_volume() {
+        let obb = Obb::from_volume(
+            Vec3::splat(2.0),
+            Vec3::splat(-1.0),
+            Quat::from_rotation_y(FRAC_PI_4),
+        );
+        assert_aabb(
+            obb.aabb(),
+            Vec3::new(-SQRT_2, -1.0, -SQRT_2),
+            Vec3::new(SQRT_2, 1.0, SQRT_2),
+        );
+        // Corner of axis aligned cube is outside of the turned one
+        assert!(!obb.contains(Vec3::new(0.95, 0.0, 0.95)));
+        assert!(obb.contains(Vec3::new(1.4, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn union_and_contains() {
+        let a = Aabb::new(Vec3::ZERO, Vec3::ONE);
+        let b = Aabb::new(Vec3::new(2.0, -1.0, 0.5), Vec3::new(3.0, 0.5, 4.0));
+        let u = a.union(&b);
+        assert_aabb(u, Vec3::new(0.0, -1.0, 0.0), Vec3::new(3.0, 1.0, 4.0));
+        assert!(a.contains(Vec3::ONE));
+        assert!(a.contains(Vec3::splat(0.5)));
+        assert!(!a.contains(Vec3::new(0.5, 1.1, 0.5)));
+        assert!(u.contains(Vec3::new(2.5, -0.5, 3.0)));
+        assert_eq!(a.intersection(&b), None);
+
+        let empty = Aabb::empty();
+        assert!(empty.is_empty());
+        assert!(!empty.contains(Vec3::ZERO));
+        assert_eq!(empty.union(&a), a);
+        assert_eq!(empty.size(), Vec3::ZERO);
+    }
+}
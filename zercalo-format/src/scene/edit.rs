@@ -0,0 +1,295 @@
+use super::grid::{grid_index, GridCoords};
+use super::model::Model;
+use crate::color::ColorRGBA;
+use glam::{IVec3, UVec3, Vec3};
+
+/// Axis of model voxel grid
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    /// Index of component in vectors
+    pub fn index(&self) -> usize {
+        match self {
+            Axis::X => 0,
+            Axis::Y => 1,
+            Axis::Z => 2,
+        }
+    }
+}
+
+/// Constructive solid geometry operation between two models
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CsgOp {
+    /// Add voxels of other model, grid is extended to hold both models
+    Union,
+    /// Remove voxels that are occupied in other model
+    Subtract,
+    /// Keep only voxels that are occupied in both models
+    Intersect,
+}
+
+impl Model {
+    /// Check that point is inside voxel grid
+    #[inline]
+    pub fn contains(&self, p: IVec3) -> bool {
+        p.min_element() >= 0 && p.cmplt(self.size.as_ivec3()).all()
+    }
+
+    /// Fill box from `min` (inclusive) to `max` (exclusive), parts outside of grid are skipped
+    pub fn fill_box(&mut self, min: IVec3, max: IVec3, color: ColorRGBA) {
+        let min = min.max(IVec3::ZERO).as_uvec3();
+        let max = max.min(self.size.as_ivec3()).max(IVec3::ZERO).as_uvec3();
+        for z in min.z..max.z {
+            for y in min.y..max.y {
+                for x in min.x..max.x {
+                    self.set_voxel(UVec3::new(x, y, z), color);
+                }
+            }
+        }
+    }
+
+    /// Fill voxels which centers are inside of sphere
+    pub fn fill_sphere(&mut self, center: Vec3, radius: f32, color: ColorRGBA) {
+        let min = (center - radius).floor().as_ivec3();
+        let max = (center + radius).ceil().as_ivec3();
+        self.fill_where(min, max, color, |p| {
+            p.distance_squared(center) <= radius * radius
+        });
+    }
+
+    /// Fill voxels which centers are inside of cylinder that stands on `base` point and goes
+    /// along positive direction of `axis` for `height` voxels.
+    pub fn fill_cylinder(
+        &mut self,
+        base: Vec3,
+        axis: Axis,
+        radius: f32,
+        height: f32,
+        color: ColorRGBA,
+    ) {
+        let d = axis.index();
+        let mut top = base;
+        top[d] += height;
+        let extent = Vec3::splat(radius);
+        let min = (base.min(top) - extent).floor().as_ivec3();
+        let max = (base.max(top) + extent).ceil().as_ivec3();
+        self.fill_where(min, max, color, |p| {
+            let mut radial = p - base;
+            let along = radial[d];
+            radial[d] = 0.0;
+            along >= 0.0 && along <= height && radial.length_squared() <= radius * radius
+        });
+    }
+
+    /// Fill voxels in the box which centers pass the predicate
+    fn fill_where<F: Fn(Vec3) -> bool>(
+        &mut self,
+        min: IVec3,
+        max: IVec3,
+        color: ColorRGBA,
+        predicate: F,
+    ) {
+        let min = min.max(IVec3::ZERO).as_uvec3();
+        let max = max.min(self.size.as_ivec3()).max(IVec3::ZERO).as_uvec3();
+        for z in min.z..max.z {
+            for y in min.y..max.y {
+                for x in min.x..max.x {
+                    let p = UVec3::new(x, y, z);
+                    if predicate(p.as_vec3() + Vec3::splat(0.5)) {
+                        self.set_voxel(p, color);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Apply CSG operation with other model. Models are aligned by their offsets rounded to
+    /// whole voxels, rotations are ignored.
+    pub fn csg(&mut self, other: &Model, op: CsgOp) {
+        let mut delta = (other.offset - self.offset).round().as_ivec3();
+        match op {
+            CsgOp::Union => {
+                let min = delta.min(IVec3::ZERO);
+                let max = (delta + other.size.as_ivec3()).max(self.size.as_ivec3());
+                self.reframe(min, (max - min).as_uvec3());
+                delta -= min;
+                self.paste_with(other, delta, true);
+            }
+            CsgOp::Subtract => {
+                for_each_voxel(other.size, |p| {
+                    let q = p.as_ivec3() + delta;
+                    if !other.get_voxel(p).is_empty() && self.contains(q) {
+                        self.set_voxel(q.as_uvec3(), ColorRGBA::empty());
+                    }
+                });
+            }
+            CsgOp::Intersect => {
+                for_each_voxel(self.size, |p| {
+                    let q = p.as_ivec3() - delta;
+                    if !other.contains(q) || other.get_voxel(q.as_uvec3()).is_empty() {
+                        self.set_voxel(p, ColorRGBA::empty());
+                    }
+                });
+            }
+        }
+    }
+
+    /// Replace color of connected (by faces) region of voxels with the same color as voxel at
+    /// `start`. Returns amount of changed voxels, nothing is changed when `start` is outside of
+    /// the model.
+    pub fn flood_fill(&mut self, start: UVec3, color: ColorRGBA) -> usize {
+        if !self.contains(start.as_ivec3()) {
+            return 0;
+        }
+        let target = self.get_voxel(start);
+        if target == color {
+            return 0;
+        }
+        let mut filled = 0;
+        let mut stack = vec![start.as_ivec3()];
+        self.set_voxel(start, color);
+        while let Some(p) = stack.pop() {
+            filled += 1;
+            for d in 0..3 {
+                for sign in [-1, 1] {
+                    let mut n = p;
+                    n[d] += sign;
+                    if self.contains(n) && self.get_voxel(n.as_uvec3()) == target {
+                        self.set_voxel(n.as_uvec3(), color);
+                        stack.push(n);
+                    }
+                }
+            }
+        }
+        filled
+    }
+
+    /// Mirror voxels along the axis, size and offset are kept
+    pub fn mirror(&mut self, axis: Axis) {
+        let d = axis.index();
+        let mut voxels = self.voxels.clone();
+        for_each_voxel(self.size, |p| {
+            let mut q = p;
+            q[d] = self.size[d] - 1 - p[d];
+            voxels[grid_index(self.size, q)] = self.get_voxel(p);
+        });
+        self.voxels = voxels;
+    }
+
+    /// Rotate voxel grid around the axis by `turns` quarter turns counter clockwise when looking
+    /// from positive end of the axis. Grid stays in positive octant and offset is kept.
+    pub fn rotate90(&mut self, axis: Axis, turns: i32) {
+        let d = axis.index();
+        let u = (d + 1) % 3;
+        let v = (d + 2) % 3;
+        for _ in 0..turns.rem_euclid(4) {
+            let mut size = self.size;
+            size[u] = self.size[v];
+            size[v] = self.size[u];
+            let mut voxels = vec![ColorRGBA::empty(); self.voxels.len()];
+            for_each_voxel(self.size, |p| {
+                let mut q = p;
+                q[u] = self.size[v] - 1 - p[v];
+                q[v] = p[u];
+                voxels[grid_index(size, q)] = self.get_voxel(p);
+            });
+            self.size = size;
+            self.voxels = voxels;
+        }
+    }
+
+    /// Get bounds of non empty voxels as minimum (inclusive) and maximum (exclusive) corners
+    pub fn occupied_bounds(&self) -> Option<(UVec3, UVec3)> {
+        let mut bounds: Option<(UVec3, UVec3)> = None;
+        for_each_voxel(self.size, |p| {
+            if !self.get_voxel(p).is_empty() {
+                let (min, max) = bounds.unwrap_or((p, p + UVec3::ONE));
+                bounds = Some((min.min(p), max.max(p + UVec3::ONE)));
+            }
+        });
+        bounds
+    }
+
+    /// Shrink grid to occupied voxels, offset is changed to keep voxels in place. Empty model
+    /// is left untouched.
+    pub fn crop(&mut self) {
+        if let Some((min, max)) = self.occupied_bounds() {
+            self.reframe(min.as_ivec3(), max - min);
+        }
+    }
+
+    /// Add empty voxels before (`min`) and after (`max`) existing ones along each axis, offset
+    /// is changed to keep voxels in place.
+    pub fn pad(&mut self, min: UVec3, max: UVec3) {
+        self.reframe(-min.as_ivec3(), self.size + min + max);
+    }
+
+    /// Change size of grid keeping voxels at the origin, voxels out of new size are dropped
+    pub fn resize(&mut self, size: UVec3) {
+        self.reframe(IVec3::ZERO, size);
+    }
+
+    /// Copy region of voxels into new model. Offset of the model places it at the same location
+    /// as the region.
+    pub fn copy_region(&self, min: UVec3, size: UVec3) -> Model {
+        let mut region = self.clone();
+        region.reframe(min.as_ivec3(), size);
+        region
+    }
+
+    /// Overwrite voxels of the model with voxels of other one placed at `at`, including empty
+    /// voxels. Parts outside of the grid are skipped.
+    pub fn paste(&mut self, other: &Model, at: IVec3) {
+        self.paste_with(other, at, false);
+    }
+
+    fn paste_with(&mut self, other: &Model, at: IVec3, skip_empty: bool) {
+        for_each_voxel(other.size, |p| {
+            let q = p.as_ivec3() + at;
+            let c = other.get_voxel(p);
+            if self.contains(q) && !(skip_empty && c.is_empty()) {
+                self.set_voxel(q.as_uvec3(), c);
+            }
+        });
+    }
+
+    /// Make new grid of given size, which origin is at `origin` in current grid coordinates.
+    /// Offset is changed to keep voxels in place.
+    fn reframe(&mut self, origin: IVec3, size: UVec3) {
+        let mut framed = Model {
+            offset: self.offset + origin.as_vec3(),
+            ..Model::new(size)
+        };
+        framed.paste(self, -origin);
+        self.size = size;
+        self.voxels = framed.voxels;
+        self.offset = framed.offset;
+    }
+}
+
+/// Iterate over all points of grid with given size in memory order
+fn for_each_voxel<F: FnMut(UVec3)>(size: UVec3, f: F) {
+    GridCoords::new(size).for_each(f);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flood_fill_outside_changes_nothing() {
+        let mut model = Model::from_function(UVec3::splat(2), |_| ColorRGBA::white());
+        assert_eq!(model.flood_fill(UVec3::new(2, 0, 0), ColorRGBA::black()), 0);
+        assert_eq!(
+            model.flood_fill(UVec3::splat(u32::MAX), ColorRGBA::black()),
+            0
+        );
+        assert!(model.voxels.iter().all(|c| *c == ColorRGBA::white()));
+        assert_eq!(model.flood_fill(UVec3::ZERO, ColorRGBA::black()), 8);
+    }
+}
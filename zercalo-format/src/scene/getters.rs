@@ -1,4 +1,6 @@
+use super::bounding::Aabb;
 use super::camera::Camera;
+use super::model::Model;
 use super::Scene;
 use glam::Vec3;
 
@@ -24,6 +26,12 @@ impl HasMutScene for Scene {
     }
 }
 
+impl<T: HasScene + ?Sized> HasScene for Box<T> {
+    fn get_scene(&self) -> &Scene {
+        self.as_ref().get_scene()
+    }
+}
+
 /// Trait that allows to access substate with camera
 pub trait HasCamera {
     fn get_camera(&self) -> &Camera;
@@ -48,20 +56,38 @@ impl HasMutCamera for Scene {
 
 /// Trait that allows to access bounding volume of inner scene
 pub trait HasBounding {
-    fn get_bounding_volume(&self) -> (Vec3, Vec3);
+    fn get_bounding_volume(&self) -> Aabb;
 
     fn get_bounding_center(&self) -> Vec3 {
-        let (minv, maxv) = self.get_bounding_volume();
-        (maxv - minv) * 0.5
+        self.get_bounding_volume().center()
     }
 }
 
 impl HasBounding for Scene {
-    fn get_bounding_volume(&self) -> (Vec3, Vec3) {
+    fn get_bounding_volume(&self) -> Aabb {
         self.bounding()
     }
+}
 
-    fn get_bounding_center(&self) -> Vec3 {
-        self.center()
+/// Trait for animated values that consist of models, allows to collect them in world space
+pub trait HasModels {
+    fn get_models(&self) -> Vec<Model>;
+}
+
+impl HasModels for Scene {
+    fn get_models(&self) -> Vec<Model> {
+        self.models.clone()
+    }
+}
+
+impl HasModels for Model {
+    fn get_models(&self) -> Vec<Model> {
+        vec![self.clone()]
+    }
+}
+
+impl<T: HasModels + ?Sized> HasModels for Box<T> {
+    fn get_models(&self) -> Vec<Model> {
+        self.as_ref().get_models()
     }
 }
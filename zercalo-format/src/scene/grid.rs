@@ -0,0 +1,388 @@
+use super::indexed::IndexedModel;
+use super::model::Model;
+use crate::color::ColorRGBA;
+use glam::UVec3;
+use std::collections::HashMap;
+
+/// Size of cubic chunk of [`ChunkedGrid`] along each axis
+pub const CHUNK_SIZE: u32 = 16;
+
+/// Storage of voxel volume. All implementations share the same coordinates and the same dense
+/// layout, where voxel at `p` has index `p.x + p.y * size.x + p.z * size.x * size.y`, so
+/// iterators of all backends yield voxels in the same order.
+pub trait VoxelGrid {
+    /// Size of the grid along each axis
+    fn size(&self) -> UVec3;
+
+    /// Get voxel color, `None` outside of the grid
+    fn get(&self, p: UVec3) -> Option<ColorRGBA>;
+
+    /// Set voxel color, returns `false` if voxel is outside of the grid and nothing changed
+    fn set(&mut self, p: UVec3, color: ColorRGBA) -> bool;
+
+    /// Get voxel color, empty outside of the grid
+    fn voxel(&self, p: UVec3) -> ColorRGBA {
+        self.get(p).unwrap_or_else(ColorRGBA::empty)
+    }
+
+    /// Iterate over all coordinates of the grid in layout order
+    fn coords(&self) -> GridCoords {
+        GridCoords::new(self.size())
+    }
+
+    /// Iterate over non empty voxels with their coordinates in layout order
+    fn occupied(&self) -> Box<dyn Iterator<Item = (UVec3, ColorRGBA)> + '_> {
+        Box::new(self.coords().filter_map(move |p| {
+            let c = self.voxel(p);
+            if c.is_empty() {
+                None
+            } else {
+                Some((p, c))
+            }
+        }))
+    }
+}
+
+/// Index of voxel in dense layout that is shared by all grids
+#[inline]
+pub fn grid_index(size: UVec3, p: UVec3) -> usize {
+    p.x as usize + p.y as usize * size.x as usize + p.z as usize * size.x as usize * size.y as usize
+}
+
+/// Copy all non empty voxels from one grid to another
+pub fn copy_grid<G: VoxelGrid + ?Sized, H: VoxelGrid + ?Sized>(from: &G, to: &mut H) {
+    for (p, c) in from.occupied() {
+        to.set(p, c);
+    }
+}
+
+/// Iterator over coordinates of grid with x changing the fastest and z the slowest
+#[derive(Clone, Debug)]
+pub struct GridCoords {
+    size: UVec3,
+    next: Option<UVec3>,
+}
+
+impl GridCoords {
+    pub fn new(size: UVec3) -> Self {
+        let next = if size.min_element() == 0 {
+            None
+        } else {
+            Some(UVec3::ZERO)
+        };
+        GridCoords { size, next }
+    }
+}
+
+impl Iterator for GridCoords {
+    type Item = UVec3;
+
+    fn next(&mut self) -> Option<UVec3> {
+        let current = self.next?;
+        let mut next = current;
+        next.x += 1;
+        if next.x >= self.size.x {
+            next.x = 0;
+            next.y += 1;
+            if next.y >= self.size.y {
+                next.y = 0;
+                next.z += 1;
+            }
+        }
+        self.next = if next.z >= self.size.z {
+            None
+        } else {
+            Some(next)
+        };
+        Some(current)
+    }
+}
+
+/// [`Model`] is the dense backend that stores each voxel in the flat vector
+impl VoxelGrid for Model {
+    fn size(&self) -> UVec3 {
+        self.size
+    }
+
+    fn get(&self, p: UVec3) -> Option<ColorRGBA> {
+        if p.cmplt(self.size).all() {
+            self.voxels.get(grid_index(self.size, p)).copied()
+        } else {
+            None
+        }
+    }
+
+    fn set(&mut self, p: UVec3, color: ColorRGBA) -> bool {
+        if !p.cmplt(self.size).all() {
+            return false;
+        }
+        match self.voxels.get_mut(grid_index(self.size, p)) {
+            Some(v) => {
+                *v = color;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Model {
+    /// Make dense model from voxels of any grid
+    pub fn from_grid<G: VoxelGrid + ?Sized>(grid: &G) -> Self {
+        let mut model = Model::new(grid.size());
+        copy_grid(grid, &mut model);
+        model
+    }
+}
+
+/// Colors of indexed model are set through palette, setting fails when the color is not in
+/// palette and palette is full.
+impl VoxelGrid for IndexedModel {
+    fn size(&self) -> UVec3 {
+        self.size
+    }
+
+    fn get(&self, p: UVec3) -> Option<ColorRGBA> {
+        if p.cmplt(self.size).all() {
+            self.indices
+                .get(grid_index(self.size, p))
+                .map(|i| self.color(*i))
+        } else {
+            None
+        }
+    }
+
+    fn set(&mut self, p: UVec3, color: ColorRGBA) -> bool {
+        if !p.cmplt(self.size).all() {
+            return false;
+        }
+        match self.palette_index(color) {
+            Some(i) => {
+                self.set_voxel(p, i);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Grid that stores only non empty voxels in hash map. Fits large volumes with few voxels.
+#[derive(Clone, Debug, Default)]
+pub struct SparseGrid {
+    pub size: UVec3,
+    pub voxels: HashMap<UVec3, ColorRGBA>,
+}
+
+impl SparseGrid {
+    /// Create new empty grid of given size
+    pub fn new(size: UVec3) -> Self {
+        SparseGrid {
+            size,
+            voxels: HashMap::new(),
+        }
+    }
+
+    /// Make sparse grid from voxels of any grid
+    pub fn from_grid<G: VoxelGrid + ?Sized>(grid: &G) -> Self {
+        let mut sparse = SparseGrid::new(grid.size());
+        copy_grid(grid, &mut sparse);
+        sparse
+    }
+}
+
+impl VoxelGrid for SparseGrid {
+    fn size(&self) -> UVec3 {
+        self.size
+    }
+
+    fn get(&self, p: UVec3) -> Option<ColorRGBA> {
+        if p.cmplt(self.size).all() {
+            Some(
+                self.voxels
+                    .get(&p)
+                    .copied()
+                    .unwrap_or_else(ColorRGBA::empty),
+            )
+        } else {
+            None
+        }
+    }
+
+    fn set(&mut self, p: UVec3, color: ColorRGBA) -> bool {
+        if !p.cmplt(self.size).all() {
+            return false;
+        }
+        if color.is_empty() {
+            self.voxels.remove(&p);
+        } else {
+            self.voxels.insert(p, color);
+        }
+        true
+    }
+
+    fn occupied(&self) -> Box<dyn Iterator<Item = (UVec3, ColorRGBA)> + '_> {
+        let mut voxels: Vec<(UVec3, ColorRGBA)> =
+            self.voxels.iter().map(|(p, c)| (*p, *c)).collect();
+        voxels.sort_by_key(|(p, _)| grid_index(self.size, *p));
+        Box::new(voxels.into_iter())
+    }
+}
+
+/// Grid that is split into cubic chunks of [`CHUNK_SIZE`] voxels, only chunks with voxels are
+/// allocated. Each chunk uses the same dense layout inside.
+#[derive(Clone, Debug, Default)]
+pub struct ChunkedGrid {
+    pub size: UVec3,
+    pub chunks: HashMap<UVec3, Vec<ColorRGBA>>,
+}
+
+impl ChunkedGrid {
+    /// Create new empty grid of given size
+    pub fn new(size: UVec3) -> Self {
+        ChunkedGrid {
+            size,
+            chunks: HashMap::new(),
+        }
+    }
+
+    /// Make chunked grid from voxels of any grid
+    pub fn from_grid<G: VoxelGrid + ?Sized>(grid: &G) -> Self {
+        let mut chunked = ChunkedGrid::new(grid.size());
+        copy_grid(grid, &mut chunked);
+        chunked
+    }
+
+    /// Release chunks that have no voxels
+    pub fn shrink(&mut self) {
+        self.chunks.retain(|_, c| c.iter().any(|v| !v.is_empty()));
+    }
+
+    #[inline]
+    fn locate(p: UVec3) -> (UVec3, usize) {
+        let chunk = p / CHUNK_SIZE;
+        let local = p - chunk * CHUNK_SIZE;
+        (chunk, grid_index(UVec3::splat(CHUNK_SIZE), local))
+    }
+}
+
+impl VoxelGrid for ChunkedGrid {
+    fn size(&self) -> UVec3 {
+        self.size
+    }
+
+    fn get(&self, p: UVec3) -> Option<ColorRGBA> {
+        if !p.cmplt(self.size).all() {
+            return None;
+        }
+        let (chunk, i) = ChunkedGrid::locate(p);
+        Some(
+            self.chunks
+                .get(&chunk)
+                .map(|c| c[i])
+                .unwrap_or_else(ColorRGBA::empty),
+        )
+    }
+
+    fn set(&mut self, p: UVec3, color: ColorRGBA) -> bool {
+        if !p.cmplt(self.size).all() {
+            return false;
+        }
+        let (chunk, i) = ChunkedGrid::locate(p);
+        match self.chunks.get_mut(&chunk) {
+            Some(c) => c[i] = color,
+            None if color.is_empty() => (),
+            None => {
+                let mut c = vec![ColorRGBA::empty(); CHUNK_SIZE.pow(3) as usize];
+                c[i] = color;
+                self.chunks.insert(chunk, c);
+            }
+        }
+        true
+    }
+
+    fn occupied(&self) -> Box<dyn Iterator<Item = (UVec3, ColorRGBA)> + '_> {
+        let chunk_coords = GridCoords::new(UVec3::splat(CHUNK_SIZE));
+        let mut voxels: Vec<(UVec3, ColorRGBA)> = self
+            .chunks
+            .iter()
+            .flat_map(|(chunk, c)| {
+                let base = *chunk * CHUNK_SIZE;
+                chunk_coords
+                    .clone()
+                    .zip(c.iter())
+                    .filter(|(_, v)| !v.is_empty())
+                    .map(move |(p, v)| (base + p, *v))
+            })
+            .collect();
+        voxels.sort_by_key(|(p, _)| grid_index(self.size, *p));
+        Box::new(voxels.into_iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Grid size that crosses chunk boundaries along every axis
+    fn size() -> UVec3 {
+        UVec3::new(CHUNK_SIZE + 3, CHUNK_SIZE, 2 * CHUNK_SIZE + 1)
+    }
+
+    fn pattern(p: UVec3) -> ColorRGBA {
+        let i = p.x * 7 + p.y * 3 + p.z * 5;
+        if i % 4 == 1 {
+            ColorRGBA::empty()
+        } else {
+            ColorRGBA::new((i % 11) as u8 * 20, (i % 13) as u8 * 15, 100, 255)
+        }
+    }
+
+    fn fill<G: VoxelGrid>(mut grid: G) -> G {
+        for p in GridCoords::new(size()) {
+            assert!(grid.set(p, pattern(p)), "failed to set {}", p);
+        }
+        grid
+    }
+
+    #[test]
+    fn backends_agree() {
+        let size = size();
+        let dense = Model::from_function(size, pattern);
+        let grids: Vec<Box<dyn VoxelGrid>> = vec![
+            Box::new(fill(Model::new(size))),
+            Box::new(fill(IndexedModel::new(size))),
+            Box::new(fill(SparseGrid::new(size))),
+            Box::new(fill(ChunkedGrid::new(size))),
+            Box::new(SparseGrid::from_grid(&dense)),
+            Box::new(ChunkedGrid::from_grid(&dense)),
+        ];
+        let expected: Vec<(UVec3, ColorRGBA)> = dense.occupied().collect();
+        for grid in grids.iter() {
+            assert_eq!(grid.size(), size);
+            for p in GridCoords::new(size) {
+                assert_eq!(grid.get(p), Some(pattern(p)), "at {}", p);
+                assert_eq!(dense.voxels[grid_index(size, p)], pattern(p));
+            }
+            assert_eq!(grid.get(UVec3::new(size.x, 0, 0)), None);
+            assert_eq!(grid.get(size), None);
+            assert_eq!(grid.occupied().collect::<Vec<_>>(), expected);
+            assert_eq!(Model::from_grid(grid.as_ref()).voxels, dense.voxels);
+        }
+    }
+
+    #[test]
+    fn set_outside_fails() {
+        let size = size();
+        let mut chunked = ChunkedGrid::new(size);
+        assert!(!chunked.set(size, ColorRGBA::white()));
+        assert!(chunked.chunks.is_empty());
+        assert!(chunked.set(size - UVec3::ONE, ColorRGBA::white()));
+        assert!(chunked.set(size - UVec3::ONE, ColorRGBA::empty()));
+        chunked.shrink();
+        assert!(chunked.chunks.is_empty());
+
+        let mut sparse = SparseGrid::new(size);
+        assert!(!sparse.set(UVec3::new(0, size.y, 0), ColorRGBA::white()));
+        assert!(sparse.voxels.is_empty());
+    }
+}
@@ -0,0 +1,281 @@
+use super::bounding::{Aabb, Obb};
+use super::model::Model;
+use crate::color::{remap_color, ColorRGBA, RemapRule};
+use glam::f32::Quat;
+use glam::{UVec3, Vec3};
+use std::collections::HashMap;
+use std::ops::Index;
+use thiserror::Error;
+
+/// Maximum amount of colors in palette of [`IndexedModel`]
+pub const INDEXED_PALETTE_SIZE: usize = 256;
+
+#[derive(Debug, Error)]
+pub enum IndexedModelError {
+    #[error("Model has {0} colors, but indexed model can hold only 256")]
+    TooManyColors(usize),
+}
+
+/// Model that stores index into palette for each voxel instead of full color. Recoloring of
+/// indexed model changes only palette, so it takes time proportional to palette size and not
+/// to amount of voxels. Empty voxels are just palette entries with zero alpha, by convention
+/// the first entry of palette is empty when the model has empty voxels.
+#[derive(Clone, Debug)]
+pub struct IndexedModel {
+    pub size: UVec3,
+    pub indices: Vec<u8>,
+    pub palette: Vec<ColorRGBA>,
+    pub offset: Vec3,
+    pub rotation: Quat,
+}
+
+impl Default for IndexedModel {
+    fn default() -> Self {
+        IndexedModel {
+            size: UVec3::new(1, 1, 1),
+            indices: vec![],
+            palette: vec![ColorRGBA::empty()],
+            offset: Vec3::ZERO,
+            rotation: Quat::from_axis_angle(Vec3::Y, 0.0),
+        }
+    }
+}
+
+impl IndexedModel {
+    /// Create new empty model with given size of voxel grid
+    pub fn new(size: UVec3) -> Self {
+        IndexedModel {
+            size,
+            indices: vec![0; size.x as usize * size.y as usize * size.z as usize],
+            ..IndexedModel::default()
+        }
+    }
+
+    /// Convert RGBA model without loss of colors, [`IndexedModel::to_model`] gives the same
+    /// voxels back. Empty color takes index 0 only when the model has empty voxels, so model with
+    /// 256 solid colors fits. Color replacements and remap rules of the model are not applied,
+    /// pass them to [`IndexedModel::replace_colors`] and [`IndexedModel::remap`] or use
+    /// [`IndexedModel::from_model_baked`]. Fails if model has more than 256 colors.
+    pub fn from_model(model: &Model) -> Result<Self, IndexedModelError> {
+        let mut palette = vec![];
+        let mut mapping: HashMap<ColorRGBA, u8> = HashMap::new();
+        if model.voxels.contains(&ColorRGBA::empty()) {
+            palette.push(ColorRGBA::empty());
+            mapping.insert(ColorRGBA::empty(), 0);
+        }
+        let mut indices = Vec::with_capacity(model.voxels.len());
+        for v in model.voxels.iter() {
+            let index = match mapping.get(v) {
+                Some(i) => *i,
+                None => {
+                    if palette.len() >= INDEXED_PALETTE_SIZE {
+                        let mut colors: Vec<&ColorRGBA> = model.voxels.iter().collect();
+                        colors.sort();
+                        colors.dedup();
+                        return Err(IndexedModelError::TooManyColors(colors.len()));
+                    }
+                    let i = palette.len() as u8;
+                    palette.push(*v);
+                    mapping.insert(*v, i);
+                    i
+                }
+            };
+            indices.push(index);
+        }
+        if palette.is_empty() {
+            palette.push(ColorRGBA::empty());
+        }
+        Ok(IndexedModel {
+            size: model.size,
+            indices,
+            palette,
+            offset: model.offset,
+            rotation: model.rotation,
+        })
+    }
+
+    /// Convert RGBA model with its color replacements and remap rules baked into palette, so
+    /// indexed model renders the same way as the source model
+    pub fn from_model_baked(model: &Model) -> Result<Self, IndexedModelError> {
+        let mut indexed = IndexedModel::from_model(model)?;
+        for c in indexed.palette.iter_mut() {
+            *c = model.rendered_color(c);
+        }
+        Ok(indexed)
+    }
+
+    /// Convert back to RGBA model
+    pub fn to_model(&self) -> Model {
+        Model {
+            size: self.size,
+            voxels: self.indices.iter().map(|i| self.color(*i)).collect(),
+            offset: self.offset,
+            rotation: self.rotation,
+            ..Model::default()
+        }
+    }
+
+    /// Get color of palette entry, indices outside of palette are empty
+    #[inline]
+    pub fn color(&self, index: u8) -> ColorRGBA {
+        self.palette
+            .get(index as usize)
+            .copied()
+            .unwrap_or_else(ColorRGBA::empty)
+    }
+
+    /// Set voxel palette index at given local coords, panics on boundary violation
+    pub fn set_voxel(&mut self, p: UVec3, index: u8) {
+        let i = p.x + p.y * self.size.x + p.z * self.size.x * self.size.y;
+        self.indices[i as usize] = index;
+    }
+
+    /// Get voxel palette index at given local coords, panics on boundary violation
+    pub fn get_index(&self, p: UVec3) -> u8 {
+        let i = p.x + p.y * self.size.x + p.z * self.size.x * self.size.y;
+        self.indices[i as usize]
+    }
+
+    /// Get voxel color at given local coords, panics on boundary violation
+    pub fn get_voxel(&self, p: UVec3) -> ColorRGBA {
+        self.color(self.get_index(p))
+    }
+
+    /// Get oriented bounding box of the model in world space
+    pub fn obb(&self) -> Obb {
+        Obb::from_volume(self.size.as_vec3(), self.offset, self.rotation)
+    }
+
+    /// Get axis aligned bounding box of the model in world space
+    pub fn aabb(&self) -> Aabb {
+        self.obb().aabb()
+    }
+
+    /// Find palette index of color or add it to palette. Returns `None` when palette is full.
+    pub fn palette_index(&mut self, color: ColorRGBA) -> Option<u8> {
+        if let Some(i) = self.palette.iter().position(|c| *c == color) {
+            return Some(i as u8);
+        }
+        if self.palette.len() >= INDEXED_PALETTE_SIZE {
+            return None;
+        }
+        self.palette.push(color);
+        Some((self.palette.len() - 1) as u8)
+    }
+
+    /// Replace colors of palette entries according to the mapping, analogue of
+    /// [`Model::replace_colors`] that is applied once instead of at each voxel.
+    pub fn replace_colors(&mut self, mapping: &HashMap<ColorRGBA, ColorRGBA>) {
+        for c in self.palette.iter_mut() {
+            *c = *mapping.get(c).unwrap_or(c);
+        }
+    }
+
+    /// Apply remap rules to palette entries, the first matching rule wins
+    pub fn remap(&mut self, rules: &[RemapRule]) {
+        for c in self.palette.iter_mut() {
+            *c = remap_color(rules, c);
+        }
+    }
+
+    /// Make a copy of the model that shares voxels layout, but uses another palette
+    pub fn with_palette(&self, palette: Vec<ColorRGBA>) -> Self {
+        IndexedModel {
+            palette,
+            ..self.clone()
+        }
+    }
+}
+
+impl Index<UVec3> for IndexedModel {
+    type Output = ColorRGBA;
+
+    fn index(&self, index: UVec3) -> &Self::Output {
+        let i = index.x + index.y * self.size.x + index.z * self.size.x * self.size.y;
+        &self.palette[self.indices[i as usize] as usize]
+    }
+}
+
+impl From<&IndexedModel> for Model {
+    fn from(model: &IndexedModel) -> Self {
+        model.to_model()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Model with given amount of distinct solid colors and one empty voxel if `with_empty`
+    fn model_with_colors(colors: usize, with_empty: bool) -> Model {
+        let mut model = Model::new(UVec3::new(colors as u32 + 1, 1, 1));
+        for i in 0..colors {
+            let c = ColorRGBA::new((i % 256) as u8, (i / 256) as u8, 7, 255);
+            model.set_voxel(UVec3::new(i as u32, 0, 0), c);
+        }
+        if !with_empty {
+            model.set_voxel(
+                UVec3::new(colors as u32, 0, 0),
+                ColorRGBA::new(0, 0, 7, 255),
+            );
+        }
+        model
+    }
+
+    fn assert_round_trip(model: &Model) {
+        let indexed = IndexedModel::from_model(model).unwrap();
+        assert!(indexed.palette.len() <= INDEXED_PALETTE_SIZE);
+        assert_eq!(indexed.to_model().voxels, model.voxels);
+    }
+
+    #[test]
+    fn round_trip_255_colors() {
+        assert_round_trip(&model_with_colors(255, true));
+        assert_round_trip(&model_with_colors(255, false));
+    }
+
+    #[test]
+    fn round_trip_256_colors() {
+        let model = model_with_colors(256, false);
+        assert!(model.voxels.iter().all(|c| !c.is_empty()));
+        let indexed = IndexedModel::from_model(&model).unwrap();
+        assert_eq!(indexed.palette.len(), 256);
+        assert!(!indexed.palette.contains(&ColorRGBA::empty()));
+        assert_round_trip(&model);
+    }
+
+    #[test]
+    fn too_many_colors() {
+        assert!(matches!(
+            IndexedModel::from_model(&model_with_colors(257, false)),
+            Err(IndexedModelError::TooManyColors(257))
+        ));
+        assert!(matches!(
+            IndexedModel::from_model(&model_with_colors(256, true)),
+            Err(IndexedModelError::TooManyColors(257))
+        ));
+    }
+
+    #[test]
+    fn empty_model_round_trip() {
+        assert_round_trip(&Model::new(UVec3::new(2, 2, 2)));
+    }
+
+    #[test]
+    fn recolor_rules_are_kept_out_of_palette() {
+        let red = ColorRGBA::new(255, 0, 0, 255);
+        let blue = ColorRGBA::new(0, 0, 255, 255);
+        let mut model = Model::from_function(UVec3::ONE, |_| red);
+        model.replace_colors.insert(red, blue);
+
+        let indexed = IndexedModel::from_model(&model).unwrap();
+        assert_eq!(indexed.to_model().voxels, vec![red]);
+
+        let mut recolored = indexed.clone();
+        recolored.replace_colors(&model.replace_colors);
+        assert_eq!(recolored.get_voxel(UVec3::ZERO), blue);
+
+        let baked = IndexedModel::from_model_baked(&model).unwrap();
+        assert_eq!(baked.get_voxel(UVec3::ZERO), blue);
+    }
+}
@@ -1,10 +1,18 @@
+pub mod bounding;
 pub mod camera;
+pub mod edit;
 pub mod getters;
+pub mod grid;
+pub mod indexed;
 pub mod light;
 pub mod model;
 
+pub use bounding::*;
 pub use camera::*;
+pub use edit::*;
 pub use getters::*;
+pub use grid::*;
+pub use indexed::*;
 pub use light::*;
 pub use model::*;
 
@@ -14,6 +22,7 @@ use glam::Vec3;
 #[derive(Clone, Debug)]
 pub struct Scene {
     pub models: Vec<Model>,
+    pub indexed_models: Vec<IndexedModel>,
     pub lights: Vec<Light>,
     pub camera: Camera,
     pub ambient: ColorRGB,
@@ -21,23 +30,17 @@ pub struct Scene {
 
 impl Scene {
     /// Get bounding volume of all scene
-    pub fn bounding(&self) -> (Vec3, Vec3) {
-        let minv = std::f32::MIN;
-        let maxv = std::f32::MAX;
-        let mut max_vec = Vec3::new(minv, minv, minv);
-        let mut min_vec = Vec3::new(maxv, maxv, maxv);
-
-        for m in self.models.iter() {
-            min_vec = min_vec.min(m.offset);
-            max_vec = max_vec.max(m.offset + m.rotation.mul_vec3(m.size.as_vec3()));
-        }
-        (min_vec, max_vec)
+    pub fn bounding(&self) -> Aabb {
+        let models = self.models.iter().map(|m| m.aabb());
+        let indexed = self.indexed_models.iter().map(|m| m.aabb());
+        models
+            .chain(indexed)
+            .fold(Aabb::empty(), |b, m| b.union(&m))
     }
 
     /// Get center of bounding volume of all scene
     pub fn center(&self) -> Vec3 {
-        let (minv, maxv) = self.bounding();
-        (maxv - minv) * 0.5
+        self.bounding().center()
     }
 }
 
@@ -45,6 +48,7 @@ impl Default for Scene {
     fn default() -> Self {
         Scene {
             models: vec![],
+            indexed_models: vec![],
             lights: vec![Light::default()],
             camera: Camera::default(),
             ambient: ColorRGB::new(25, 25, 25),
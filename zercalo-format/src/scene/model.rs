@@ -1,4 +1,5 @@
-use crate::color::ColorRGBA;
+use super::bounding::{Aabb, Obb};
+use crate::color::{remap_color, ColorRGBA, RemapRule};
 use glam::f32::Quat;
 use glam::{UVec3, Vec3};
 use rayon::prelude::*;
@@ -12,6 +13,8 @@ pub struct Model {
     pub offset: Vec3,
     pub rotation: Quat,
     pub replace_colors: HashMap<ColorRGBA, ColorRGBA>,
+    /// Rules that are applied at render time to colors that are not in `replace_colors`
+    pub remap_rules: Vec<RemapRule>,
 }
 
 impl Default for Model {
@@ -22,6 +25,7 @@ impl Default for Model {
             offset: Vec3::ZERO,
             rotation: Quat::from_axis_angle(Vec3::Y, 0.0),
             replace_colors: HashMap::new(),
+            remap_rules: vec![],
         }
     }
 }
@@ -41,22 +45,23 @@ impl Model {
     where
         F: FnMut(UVec3) -> ColorRGBA + Send + Sync + Clone,
     {
+        // Layers go along z and rows along y, so flattening gives x the fastest as in `get_voxel`
         let mut layers = vec![];
-        (0..size.x)
+        (0..size.z)
             .into_par_iter()
-            .map(|x| {
-                let mut columns = vec![];
+            .map(|z| {
+                let mut rows = vec![];
                 (0..size.y)
                     .into_par_iter()
                     .map(|y| {
-                        let mut column = vec![ColorRGBA::empty(); size.z as usize];
-                        for z in 0..size.z {
-                            column[z as usize] = generator.clone()(UVec3::new(x, y, z));
-                        }
-                        column
+                        // Each voxel gets fresh copy of generator, so state of closure never
+                        // leaks between voxels
+                        (0..size.x)
+                            .map(|x| generator.clone()(UVec3::new(x, y, z)))
+                            .collect::<Vec<ColorRGBA>>()
                     })
-                    .collect_into_vec(&mut columns);
-                columns
+                    .collect_into_vec(&mut rows);
+                rows
             })
             .collect_into_vec(&mut layers);
 
@@ -67,6 +72,7 @@ impl Model {
             offset: Vec3::new(0.0, 0.0, 0.0),
             rotation: Quat::from_axis_angle(Vec3::Y, 0.0),
             replace_colors: HashMap::new(),
+            remap_rules: vec![],
         }
     }
 
@@ -81,6 +87,68 @@ impl Model {
         let i = p.x + p.y * self.size.x + p.z * self.size.x * self.size.y;
         self.voxels[i as usize]
     }
+
+    /// Get oriented bounding box of the model in world space
+    pub fn obb(&self) -> Obb {
+        Obb::from_volume(self.size.as_vec3(), self.offset, self.rotation)
+    }
+
+    /// Get axis aligned bounding box of the model in world space
+    pub fn aabb(&self) -> Aabb {
+        self.obb().aabb()
+    }
+
+    /// Place the model into parent space that is rotated by `rotation` and then moved to
+    /// `position`. Voxels are not touched, only `offset` and `rotation` are updated.
+    pub fn transformed(&self, position: Vec3, rotation: Quat) -> Model {
+        let rotation = rotation * self.rotation;
+        Model {
+            offset: self.offset + rotation.inverse().mul_vec3(position),
+            rotation,
+            ..self.clone()
+        }
+    }
+
+    /// Get color as it is rendered, that is after `replace_colors` and `remap_rules`
+    pub fn rendered_color(&self, c: &ColorRGBA) -> ColorRGBA {
+        match self.replace_colors.get(c) {
+            Some(replaced) => *replaced,
+            None if self.remap_rules.is_empty() => *c,
+            None => remap_color(&self.remap_rules, c),
+        }
+    }
+
+    /// Get voxel color at given local coords as it is rendered, panics on boundary violation
+    pub fn get_rendered_voxel(&self, p: UVec3) -> ColorRGBA {
+        self.rendered_color(&self.get_voxel(p))
+    }
+
+    /// Apply remap rules to voxels of the model, the first matching rule wins
+    pub fn remap(&mut self, rules: &[RemapRule]) {
+        for v in self.voxels.iter_mut() {
+            *v = remap_color(rules, v);
+        }
+    }
+
+    /// Bake `replace_colors` and `remap_rules` into voxels, so renderer doesn't need to apply
+    /// them for each ray hit.
+    pub fn bake_colors(&mut self) {
+        let mut cache: HashMap<ColorRGBA, ColorRGBA> = HashMap::new();
+        for i in 0..self.voxels.len() {
+            let c = self.voxels[i];
+            let rendered = match cache.get(&c) {
+                Some(r) => *r,
+                None => {
+                    let r = self.rendered_color(&c);
+                    cache.insert(c, r);
+                    r
+                }
+            };
+            self.voxels[i] = rendered;
+        }
+        self.replace_colors.clear();
+        self.remap_rules.clear();
+    }
 }
 
 impl Index<UVec3> for Model {
@@ -8,6 +8,9 @@ use std::fs::File;
 use std::io::BufWriter;
 use std::path::Path;
 use thiserror::Error;
+use zercalo_format::animation::{LoopMode, Marker};
+
+use crate::render::RenderedClip;
 
 #[derive(Debug, Error)]
 pub enum EncodeError {
@@ -41,6 +44,7 @@ fn save_apng<'a, V: IntoIterator<Item = &'a [u8]>>(
     data: V,
     width: u32,
     height: u32,
+    num_plays: u32,
 ) -> Result<(), EncodeError> {
     let path = Path::new(str_path);
     let file = File::create(path)?;
@@ -60,7 +64,7 @@ fn save_apng<'a, V: IntoIterator<Item = &'a [u8]>>(
         width,
         height,
         num_frames: images.len() as u32,
-        num_plays: 1,
+        num_plays,
         color: png::ColorType::RGBA,
         depth: png::BitDepth::Eight,
         filter: png::FilterType::NoFilter,
@@ -81,12 +85,11 @@ fn save_apng<'a, V: IntoIterator<Item = &'a [u8]>>(
     Ok(())
 }
 
-pub fn save_frames<'a>(
+/// Read pixels of rendered frames in RGBA order
+fn read_frames(
     canvas: &mut Canvas<Window>,
-    frames: &mut [Texture<'a>],
-    tile_size: UVec2,
-    directory: &str,
-) -> Result<(), EncodeError> {
+    frames: &mut [Texture<'_>],
+) -> Result<Vec<Vec<u8>>, EncodeError> {
     let frames_count = frames.len();
     let mut textures = vec![];
     for (i, frame) in frames.iter_mut().enumerate() {
@@ -101,22 +104,137 @@ pub fn save_frames<'a>(
             .expect("Cannot read pixels from frame");
         frames_data.push(pixels);
     })?;
+    Ok(frames_data)
+}
 
-    fs::create_dir_all(format!("{}/frames/diffuse", directory))?;
+/// Write each frame as separate PNG file into the directory
+fn save_sequence(
+    directory: &str,
+    frames_data: &[Vec<u8>],
+    tile_size: UVec2,
+) -> Result<(), EncodeError> {
+    fs::create_dir_all(directory)?;
     for (i, pixels) in frames_data.iter().enumerate() {
         save_png(
-            &format!("{}/frames/diffuse/frame_{:0>4}.png", directory, i),
+            &format!("{}/frame_{:0>4}.png", directory, i),
             pixels,
             tile_size.x,
             tile_size.y,
         )?;
     }
+    Ok(())
+}
+
+pub fn save_frames<'a>(
+    canvas: &mut Canvas<Window>,
+    frames: &mut [Texture<'a>],
+    tile_size: UVec2,
+    directory: &str,
+) -> Result<(), EncodeError> {
+    let frames_data = read_frames(canvas, frames)?;
+    save_sequence(
+        &format!("{}/frames/diffuse", directory),
+        &frames_data,
+        tile_size,
+    )?;
     save_apng(
         &format!("{}/diffuse.png", directory),
         frames_data.iter().map(|v| &v[..]),
         tile_size.x,
         tile_size.y,
+        1,
     )?;
 
     Ok(())
 }
+
+/// Escape string for JSON
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn json_marker(marker: &Marker) -> String {
+    format!(
+        r#"{{"name":{},"frame":{},"payload":{}}}"#,
+        json_string(&marker.name),
+        marker.frame,
+        marker
+            .payload
+            .as_deref()
+            .map_or_else(|| "null".to_owned(), json_string),
+    )
+}
+
+/// Indices of rendered frames in the order they are played during one loop of the clip. Ping-pong
+/// clips go forward and then back through the interior frames, so the looped APNG returns to the
+/// first frame without showing the ends twice.
+fn playback_order(frames: usize, loop_mode: LoopMode) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..frames).collect();
+    if loop_mode == LoopMode::PingPong && frames > 2 {
+        order.extend((1..frames - 1).rev());
+    }
+    order
+}
+
+/// Save each clip as named sequence `frames/<clip>/diffuse/frame_NNNN.png` and animated
+/// `<clip>_diffuse.png`, ping-pong clips are played back and forth in APNG while the sequence
+/// has only the forward frames. Clips are listed in `clips.json` with their frame count, loop mode,
+/// path to the frames and markers.
+pub fn save_clips<'a>(
+    canvas: &mut Canvas<Window>,
+    clips: &mut [RenderedClip<'a>],
+    tile_size: UVec2,
+    directory: &str,
+) -> Result<(), EncodeError> {
+    let mut entries = vec![];
+    for clip in clips.iter_mut() {
+        let frames_data = read_frames(canvas, &mut clip.frames)?;
+        let path = format!("frames/{}/diffuse", clip.name);
+        save_sequence(&format!("{}/{}", directory, path), &frames_data, tile_size)?;
+        let num_plays = match clip.loop_mode {
+            LoopMode::Once => 1,
+            LoopMode::Loop | LoopMode::PingPong => 0,
+        };
+        save_apng(
+            &format!("{}/{}_diffuse.png", directory, clip.name),
+            playback_order(frames_data.len(), clip.loop_mode)
+                .into_iter()
+                .map(|i| &frames_data[i][..]),
+            tile_size.x,
+            tile_size.y,
+            num_plays,
+        )?;
+        entries.push(format!(
+            r#"{{"name":{},"frames":{},"loop":"{}","path":{},"markers":[{}]}}"#,
+            json_string(&clip.name),
+            frames_data.len(),
+            clip.loop_mode.name(),
+            json_string(&path),
+            clip.markers
+                .iter()
+                .map(json_marker)
+                .collect::<Vec<_>>()
+                .join(","),
+        ));
+    }
+
+    let metadata = format!(
+        r#"{{"tile_size":[{},{}],"clips":[{}]}}"#,
+        tile_size.x,
+        tile_size.y,
+        entries.join(",")
+    );
+    fs::write(format!("{}/clips.json", directory), metadata)?;
+    Ok(())
+}
@@ -1,16 +1,17 @@
 use fast_voxel_traversal::raycast_3d::*;
-use glam::{IVec3, UVec2, Vec3, Vec4};
+use glam::{IVec3, Quat, UVec2, Vec3, Vec4};
 use log::*;
 use rayon::prelude::*;
 use sdl2::pixels::{Color, PixelFormatEnum};
 use sdl2::rect::Point;
 use sdl2::render::{BlendMode, Canvas, Texture, TextureCreator};
 use sdl2::video::{Window, WindowContext};
+use std::ops::Range;
 use thiserror::Error;
 
-use zercalo_format::animation::Animatable;
+use zercalo_format::animation::{Animatable, Clip, HasCycle, HasMarkers, LoopMode, Marker};
 use zercalo_format::color::ColorRGBA;
-use zercalo_format::scene::HasScene;
+use zercalo_format::scene::{HasScene, Scene, VoxelGrid};
 
 #[derive(Debug, Error)]
 pub enum RenderError {
@@ -26,6 +27,242 @@ fn blend_colors(src: Vec4, dst: Vec4) -> Vec4 {
     src + dst * (1.0 - src.w)
 }
 
+/// Trace ray of the pixel through voxel grid with given rotation and offset. Colors of hit
+/// voxels pass through `recolor` before shading. Returns accumulated premultiplied color and
+/// distance where the ray stopped.
+fn trace_volume<G: VoxelGrid + ?Sized, F: Fn(&ColorRGBA) -> ColorRGBA>(
+    scene: &Scene,
+    pixel: UVec2,
+    tile_size: UVec2,
+    rotation: Quat,
+    offset: Vec3,
+    grid: &G,
+    recolor: F,
+) -> (Vec4, f32) {
+    let size = grid.size();
+    let (i, j) = (pixel.x, pixel.y);
+    let rot_quat = rotation.inverse();
+    let eye = rot_quat.mul_vec3(scene.camera.eye);
+    let up = rot_quat.mul_vec3(scene.camera.up);
+    let dir = rot_quat.mul_vec3(scene.camera.dir);
+
+    let right = dir.cross(up);
+    let pixel_offset = up * ((j as f32 - 0.5 * tile_size.y as f32) * scene.camera.pixel_size)
+        + right * ((i as f32 - 0.5 * tile_size.x as f32) * scene.camera.pixel_size);
+
+    let ray_origin = eye - offset + pixel_offset;
+    let ray = Ray3 {
+        origin: ray_origin.into(),
+        direction: dir.into(),
+        length: scene.camera.max_dist,
+    };
+
+    let volume = BoundingVolume3 {
+        size: (size.x as i32, size.y as i32, size.z as i32),
+    };
+
+    let mut model_color = Vec4::ZERO;
+    let mut model_dist = scene.camera.max_dist;
+    for hit in volume.traverse_ray(ray) {
+        let inormal: IVec3 = hit.normal.unwrap_or((1, 0, 0)).into();
+        let normal: Vec3 = inormal.as_vec3();
+        let voxel: IVec3 = hit.voxel.into();
+        let diffuse = recolor(&grid.voxel(voxel.as_uvec3())).as_premultipied();
+
+        let mut light_component = Vec3::new(0.0, 0.0, 0.0);
+        for light in scene.lights.iter() {
+            let tolight: Vec3 = (rot_quat.mul_vec3(light.position) - voxel.as_vec3()).normalize();
+            let new_component = diffuse.truncate() * light.color.as_vec3() * tolight.dot(normal);
+            light_component += new_component.max(Vec3::new(0.0, 0.0, 0.0));
+        }
+        let ambient_component = diffuse.truncate() * scene.ambient.as_vec3();
+        model_color = blend_colors(
+            model_color,
+            (ambient_component + light_component, diffuse.w).into(),
+        );
+        model_dist = (ray_origin - voxel.as_vec3()).length();
+        if model_color.w >= 1.0 {
+            break;
+        }
+    }
+    (model_color, model_dist)
+}
+
+/// Premultiplied colors of rendered frame stored by columns from left to right, each column
+/// goes from bottom to top
+pub type FrameColors = Vec<Vec<Vec4>>;
+
+/// Render single scene into colors of pixels, pixels are traced in parallel
+pub fn render_scene(scene: &Scene, tile_size: UVec2) -> FrameColors {
+    // Render columns in parallel
+    let mut columns = vec![];
+    (0..tile_size.x)
+        .into_par_iter()
+        .map(|i| {
+            let mut column = vec![];
+            (0..tile_size.y)
+                .into_par_iter()
+                .map(|j| {
+                    // Total accumulated color for model
+                    let mut total_color = Vec4::new(0.0, 0.0, 0.0, 0.0);
+                    // The last distance ray traveled until full stop. It is used to cull other models.
+                    let mut total_dist = scene.camera.max_dist;
+                    let pixel = UVec2::new(i, j);
+                    let traced = scene
+                        .models
+                        .iter()
+                        .map(|model| {
+                            trace_volume(
+                                scene,
+                                pixel,
+                                tile_size,
+                                model.rotation,
+                                model.offset,
+                                model,
+                                |c| model.rendered_color(c),
+                            )
+                        })
+                        .chain(scene.indexed_models.iter().map(|model| {
+                            trace_volume(
+                                scene,
+                                pixel,
+                                tile_size,
+                                model.rotation,
+                                model.offset,
+                                model,
+                                |c| *c,
+                            )
+                        }));
+                    for (model_color, model_dist) in traced {
+                        if model_dist <= total_dist {
+                            total_color = blend_colors(model_color, total_color);
+                            total_dist = model_dist;
+                        } else {
+                            total_color = blend_colors(total_color, model_color);
+                        }
+                    }
+                    total_color
+                })
+                .collect_into_vec(&mut column);
+            column
+        })
+        .collect_into_vec(&mut columns);
+    columns
+}
+
+/// Evaluate the context at each frame of the range on the current thread and take copies of its
+/// scene, so frames can be rendered independently
+pub fn snapshot_frames<R: Animatable + HasScene>(
+    context: &mut R,
+    frames: Range<u32>,
+) -> Vec<Scene> {
+    frames
+        .map(|frame| {
+            context.evaluate(frame);
+            context.get_scene().clone()
+        })
+        .collect()
+}
+
+/// Render several scene snapshots concurrently. Result keeps the order of snapshots and doesn't
+/// depend on scheduling of threads.
+pub fn render_snapshots(scenes: &[Scene], tile_size: UVec2) -> Vec<FrameColors> {
+    scenes
+        .par_iter()
+        .map(|scene| render_scene(scene, tile_size))
+        .collect()
+}
+
+/// Difference between two rendered frames
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct FrameDiff {
+    /// Amount of pixels that differ after conversion to 8 bit colors
+    pub pixels: usize,
+    /// Largest difference of color channel in range 0 .. 1
+    pub max: f32,
+    /// Mean difference of color channels over all pixels
+    pub mean: f32,
+}
+
+impl FrameDiff {
+    /// Frames look the same after they are saved
+    pub fn is_seamless(&self) -> bool {
+        self.pixels == 0
+    }
+}
+
+/// Compare rendered frames pixel by pixel, frames must have the same size
+pub fn frame_diff(a: &FrameColors, b: &FrameColors) -> FrameDiff {
+    let mut diff = FrameDiff::default();
+    let mut total = 0.0;
+    let mut channels = 0;
+    for (column_a, column_b) in a.iter().zip(b.iter()) {
+        for (color_a, color_b) in column_a.iter().zip(column_b.iter()) {
+            let d = (*color_a - *color_b).abs();
+            let max = d.max_element();
+            if max * 255.0 >= 1.0 {
+                diff.pixels += 1;
+            }
+            diff.max = diff.max.max(max);
+            total += d.x + d.y + d.z + d.w;
+            channels += 4;
+        }
+    }
+    if channels > 0 {
+        diff.mean = total / channels as f32;
+    }
+    diff
+}
+
+/// Render frame 0 and frame `frames` of the animation and compare them. Animation with seamless
+/// loop of `frames` frames shows frame 0 again right after its last frame.
+pub fn verify_loop<R: Animatable + HasScene>(
+    context: &mut R,
+    frames: u32,
+    tile_size: UVec2,
+) -> FrameDiff {
+    let scenes = [0, frames]
+        .iter()
+        .flat_map(|frame| snapshot_frames(context, *frame..*frame + 1))
+        .collect::<Vec<_>>();
+    let colors = render_snapshots(&scenes, tile_size);
+    frame_diff(&colors[0], &colors[1])
+}
+
+/// Frames count for seamless loop of the animation, `max_frames` of its camera is used when the
+/// animation doesn't repeat or repeats later. Loop is verified by rendering and the result is
+/// logged.
+pub fn loop_frames<R: Animatable + HasScene + HasCycle>(context: &mut R, tile_size: UVec2) -> u32 {
+    let max_frames = context.get_scene().camera.max_frames;
+    let frames = match context.cycle_frames() {
+        Some(cycle) if cycle <= max_frames => cycle,
+        Some(cycle) => {
+            warn!(
+                "Animation loops after {} frames, that is more than {} max frames",
+                cycle, max_frames
+            );
+            return max_frames;
+        }
+        None => {
+            warn!("Animation doesn't loop, using {} max frames", max_frames);
+            return max_frames;
+        }
+    };
+    let diff = verify_loop(context, frames, tile_size);
+    if diff.is_seamless() {
+        info!("Animation loops seamlessly after {} frames", frames);
+    } else {
+        warn!(
+            "Frame {} differs from frame 0: {} pixels, max difference {:.4}, mean {:.6}",
+            frames, diff.pixels, diff.max, diff.mean
+        );
+    }
+    frames
+}
+
+/// Render frames of animation into textures. Frames are processed by batches: scene is
+/// evaluated and snapshotted for each frame of the batch, snapshots are rendered in parallel and
+/// then drawn to textures in order.
 pub fn render_frames<'a, R: Animatable + HasScene>(
     canvas: &mut Canvas<Window>,
     texture_creator: &'a TextureCreator<WindowContext>,
@@ -44,111 +281,24 @@ pub fn render_frames<'a, R: Animatable + HasScene>(
         frames.push(frame);
     }
 
-    {
-        let mut textures = vec![];
-        for (i, frame) in frames.iter_mut().enumerate() {
-            textures.push((frame, i));
-        }
+    let batch_size = 2 * rayon::current_num_threads().max(1) as u32;
+    let mut batch_start = 0;
+    while batch_start < frames_count {
+        let batch_end = (batch_start + batch_size).min(frames_count);
+        info!(
+            "Rendering frames {}..{}/{}",
+            batch_start, batch_end, frames_count
+        );
+        let scenes = snapshot_frames(&mut context, batch_start..batch_end);
+        let colors = render_snapshots(&scenes, tile_size);
 
-        canvas.with_multiple_texture_canvas(textures.iter(), |texture_canvas, frame| {
-            info!("Rendering frame {}/{}", frame, frames_count);
+        let textures: Vec<_> = frames[batch_start as usize..batch_end as usize]
+            .iter_mut()
+            .zip(colors.iter())
+            .collect();
+        canvas.with_multiple_texture_canvas(textures.iter(), |texture_canvas, columns| {
             texture_canvas.set_draw_color(Color::RGBA(0, 0, 0, 0));
             texture_canvas.clear();
-            context.animate(*frame as u32);
-            let scene = context.get_scene();
-
-            // First render columns in parallel
-            let mut columns = vec![];
-            (0..tile_size.x)
-                .into_par_iter()
-                .map(|i| {
-                    let mut column = vec![];
-                    (0..tile_size.y)
-                        .into_par_iter()
-                        .map(|j| {
-                            // Total accumulated color for model
-                            let mut total_color = Vec4::new(0.0, 0.0, 0.0, 0.0);
-                            // The last distance ray traveled until full stop. It is used to cull other models.
-                            let mut total_dist = scene.camera.max_dist;
-                            for model in scene.models.iter() {
-                                let rot_quat = model.rotation.inverse();
-                                let eye = rot_quat.mul_vec3(scene.camera.eye);
-                                let up = rot_quat.mul_vec3(scene.camera.up);
-                                let dir = rot_quat.mul_vec3(scene.camera.dir);
-
-                                let right = dir.cross(up);
-                                let offset = up
-                                    * ((j as f32 - 0.5 * tile_size.y as f32)
-                                        * scene.camera.pixel_size)
-                                    + right
-                                        * ((i as f32 - 0.5 * tile_size.x as f32)
-                                            * scene.camera.pixel_size);
-
-                                let ray_origin = eye - model.offset + offset;
-                                let ray = Ray3 {
-                                    origin: ray_origin.into(),
-                                    direction: dir.into(),
-                                    length: scene.camera.max_dist,
-                                };
-
-                                let volume = BoundingVolume3 {
-                                    size: (
-                                        model.size.x as i32,
-                                        model.size.y as i32,
-                                        model.size.z as i32,
-                                    ),
-                                };
-
-                                let mut model_color = Vec4::ZERO;
-                                let mut model_dist = scene.camera.max_dist;
-                                'rayloop: for hit in volume.traverse_ray(ray) {
-                                    let inormal: IVec3 = hit.normal.unwrap_or((1, 0, 0)).into();
-                                    let normal: Vec3 = inormal.as_vec3();
-                                    let voxel: IVec3 = hit.voxel.into();
-                                    let diffuse_orig = model[voxel.as_uvec3()];
-                                    let diffuse = model
-                                        .replace_colors
-                                        .get(&diffuse_orig)
-                                        .unwrap_or(&diffuse_orig)
-                                        .as_premultipied();
-
-                                    let mut light_component = Vec3::new(0.0, 0.0, 0.0);
-                                    for light in scene.lights.iter() {
-                                        let tolight: Vec3 = (rot_quat.mul_vec3(light.position)
-                                            - voxel.as_vec3())
-                                        .normalize();
-                                        let new_component = diffuse.truncate()
-                                            * light.color.as_vec3()
-                                            * tolight.dot(normal);
-                                        light_component +=
-                                            new_component.max(Vec3::new(0.0, 0.0, 0.0));
-                                    }
-                                    let ambient_component =
-                                        diffuse.truncate() * scene.ambient.as_vec3();
-                                    model_color = blend_colors(
-                                        model_color,
-                                        (ambient_component + light_component, diffuse.w).into(),
-                                    );
-                                    model_dist = (ray_origin - voxel.as_vec3()).length();
-                                    if model_color.w >= 1.0 {
-                                        break 'rayloop;
-                                    }
-                                }
-
-                                if model_dist <= total_dist {
-                                    total_color = blend_colors(model_color, total_color);
-                                    total_dist = model_dist;
-                                } else {
-                                    total_color = blend_colors(total_color, model_color);
-                                }
-                            }
-                            total_color
-                        })
-                        .collect_into_vec(&mut column);
-                    column
-                })
-                .collect_into_vec(&mut columns);
-
             // Writing down colors to texture
             for (i, column) in columns.iter().enumerate() {
                 for (j, total_color) in column.iter().enumerate() {
@@ -162,7 +312,41 @@ pub fn render_frames<'a, R: Animatable + HasScene>(
                 }
             }
         })?;
+        batch_start = batch_end;
     }
 
     Ok(frames)
 }
+
+/// Frames of rendered [`Clip`]
+pub struct RenderedClip<'a> {
+    pub name: String,
+    pub loop_mode: LoopMode,
+    pub frames: Vec<Texture<'a>>,
+    /// Markers of the clip in its own frames
+    pub markers: Vec<Marker>,
+}
+
+/// Render each clip of the unit into its own sequence of frames
+pub fn render_clips<'a, R: Animatable + HasScene + HasMarkers>(
+    canvas: &mut Canvas<Window>,
+    texture_creator: &'a TextureCreator<WindowContext>,
+    tile_size: UVec2,
+    clips: Vec<Clip<R>>,
+) -> Result<Vec<RenderedClip<'a>>, RenderError> {
+    let mut rendered = vec![];
+    for clip in clips.into_iter() {
+        info!("Rendering clip {} of {} frames", clip.name, clip.frames);
+        let name = clip.name.clone();
+        let loop_mode = clip.loop_mode;
+        let markers = clip.get_markers(0..clip.frames);
+        let frames = render_frames(canvas, texture_creator, clip.frames, tile_size, clip)?;
+        rendered.push(RenderedClip {
+            name,
+            loop_mode,
+            frames,
+            markers,
+        });
+    }
+    Ok(rendered)
+}
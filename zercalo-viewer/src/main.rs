@@ -12,8 +12,8 @@ use crate::scenes::*;
 
 use zercalo_format::color::ColorRGBA;
 use zercalo_format::scene::HasCamera;
-use zercalo_render::encode::save_frames;
-use zercalo_render::render::render_frames;
+use zercalo_render::encode::{save_clips, save_frames};
+use zercalo_render::render::{loop_frames, render_clips, render_frames};
 
 const WINDOW_WIDTH: u32 = 1024;
 const WINDOW_HEIGHT: u32 = 1024;
@@ -40,25 +40,40 @@ fn main() -> Result<(), Box<dyn Error>> {
     canvas.clear();
     canvas.present();
 
-    // let scene = new_penetrator_scene()?;
-    // let scene = new_harvester_scene(ColorRGBA::player2())?;
-    // let scene = SmokeScene::new();
-    // let scene = SandScene::new();
-    let scene = DuneTile::new();
-    
     let mut event_pump = sdl_context.event_pump()?;
     let texture_creator: TextureCreator<_> = canvas.texture_creator();
-    let cam = scene.get_camera();
-    let tile_size = cam.viewport;
-    canvas.set_scale(cam.view_scale.x, cam.view_scale.y)?;
-    let mut frames = render_frames(
-        &mut canvas,
-        &texture_creator,
-        cam.max_frames,
-        tile_size,
-        scene,
-    )?;
-    save_frames(&mut canvas, &mut frames, tile_size, ".")?;
+
+    // With `--clips` the unit clips are rendered to separate sequences and previewed one by one
+    let (tile_size, frames) = if std::env::args().any(|arg| arg == "--clips") {
+        let clips = new_harvester_clips(ColorRGBA::player2())?;
+        let cam = clips.first().ok_or("no clips to render")?.get_camera();
+        let tile_size = cam.viewport;
+        canvas.set_scale(cam.view_scale.x, cam.view_scale.y)?;
+        let mut rendered = render_clips(&mut canvas, &texture_creator, tile_size, clips)?;
+        save_clips(&mut canvas, &mut rendered, tile_size, ".")?;
+        let frames: Vec<_> = rendered.into_iter().flat_map(|clip| clip.frames).collect();
+        (tile_size, frames)
+    } else {
+        // let scene = new_penetrator_scene()?;
+        // let scene = new_harvester_scene(ColorRGBA::player2())?;
+        // let scene = SmokeScene::new();
+        // let scene = SandScene::new();
+        let mut scene = DuneTile::new();
+
+        let cam = scene.get_camera();
+        let tile_size = cam.viewport;
+        canvas.set_scale(cam.view_scale.x, cam.view_scale.y)?;
+        let frames_count = loop_frames(&mut scene, tile_size);
+        let mut frames = render_frames(
+            &mut canvas,
+            &texture_creator,
+            frames_count,
+            tile_size,
+            scene,
+        )?;
+        save_frames(&mut canvas, &mut frames, tile_size, ".")?;
+        (tile_size, frames)
+    };
 
     let mut counter: u32 = 0;
     let mut frame = 0;
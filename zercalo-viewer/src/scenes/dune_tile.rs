@@ -1,8 +1,9 @@
 use glam::{UVec2, UVec3, Vec2, Vec3};
-use zercalo_format::animation::{Animatable, RotationView};
+use zercalo_format::animation::{Animatable, HasCycle, RotationView};
 use zercalo_format::color::{ColorRGB, ColorRGBA};
+use zercalo_format::procedure::tile::{terrain_from_heights, TerrainColoring};
 use zercalo_format::scene::{
-    Camera, HasBounding, HasCamera, HasMutCamera, HasScene, Light, Model, Scene,
+    Aabb, Camera, HasBounding, HasCamera, HasMutCamera, HasScene, Light, Scene,
 };
 
 pub struct DuneTile {
@@ -10,24 +11,9 @@ pub struct DuneTile {
     rendered: Scene,
 }
 
-/// Select value depending on the assigned weight
-fn weighted<'a, T>(rng: &mut fastrand::Rng, values: &'a [(T, f32)]) -> &'a T {
-    assert!(!values.is_empty(), "Empty weighted list");
-    let total_weight: f32 = values.iter().map(|(_, w)| w).sum();
-    let selected = rng.f32() * total_weight;
-    let mut accum = 0.0;
-    for (v, w) in values {
-        if selected < accum + w {
-            return v;
-        }
-        accum += w;
-    }
-    &values[0].0
-}
-
 impl DuneTile {
     pub fn new() -> RotationView<Self> {
-        let mut rng = fastrand::Rng::with_seed(42);
+        let rng = fastrand::Rng::with_seed(42);
         // let colors = vec![
         //     ColorRGBA::new(242, 183, 106, 100),
         //     ColorRGBA::new(232, 198, 150, 100),
@@ -38,19 +24,11 @@ impl DuneTile {
         let color3 = ColorRGBA::new(225, 145, 56, 255);
         let colors = vec![(color1, 0.8), (color2, 0.1), (color3, 0.1)];
         let size = UVec3::new(64, 64, 64);
-        let mut model = Model::new(size);
-        for i in 0..size.x {
-            for j in 0..size.z {
-                let height =
-                    (10.0 + 0.05 * f32::sin(7.0 * i as f32 / size.x as f32) * size.y as f32).round().max(1.0) as u32;
-                for z in 0 .. height {
-                    model.set_voxel(
-                        UVec3::new(i, z, j),
-                        weighted(&mut rng, &colors).clone(),
-                    );
-                }
-            }
-        }
+        let model = terrain_from_heights(&rng, size, &TerrainColoring::Weighted(colors), |i, _| {
+            (10.0 + 0.05 * f32::sin(7.0 * i as f32 / size.x as f32) * size.y as f32)
+                .round()
+                .max(1.0) as u32
+        });
 
         let eye = Vec3::new(256., 256., 256.);
         let scene = Scene {
@@ -71,12 +49,8 @@ impl DuneTile {
             ..Scene::default()
         };
         let mut sand_scene = DuneTile { rendered: scene };
-        sand_scene.animate(0);
-        RotationView {
-            scene: sand_scene,
-            target_y: Some(0.0),
-            rotation_speed: 0.0,
-        } // std::f32::consts::PI / 180.0
+        sand_scene.evaluate(0);
+        RotationView::new(sand_scene, Some(0.0), 0.0) // std::f32::consts::PI / 180.0
     }
 }
 
@@ -99,11 +73,17 @@ impl HasScene for DuneTile {
 }
 
 impl Animatable for DuneTile {
-    fn animate(&mut self, frame: u32) {}
+    fn evaluate(&mut self, frame: u32) {}
 }
 
 impl HasBounding for DuneTile {
-    fn get_bounding_volume(&self) -> (Vec3, Vec3) {
+    fn get_bounding_volume(&self) -> Aabb {
         self.rendered.bounding()
     }
 }
+
+impl HasCycle for DuneTile {
+    fn cycle_frames(&self) -> Option<u32> {
+        Some(1)
+    }
+}
@@ -1,17 +1,18 @@
-use glam::{UVec2, Vec2, Vec3};
+use glam::{Quat, UVec2, Vec2, Vec3};
 use maplit::hashmap;
-use zercalo_format::animation::{Animatable, RotationView, Switcher};
-use zercalo_format::color::{ColorRGB, ColorRGBA};
-use zercalo_format::import::vox::{from_vox_file, VoxImportError};
+use std::ops::Range;
+use zercalo_format::animation::{
+    Animatable, Clip, Composition, HasCycle, HasMarkers, LoopMode, Marked, Marker, Part,
+    RotationView, Switcher,
+};
+use zercalo_format::color::{ColorMatch, ColorRGB, ColorRGBA, ColorTarget, RemapRule};
+use zercalo_format::import::vox::{from_vox_file, from_vox_sequence, VoxImportError};
 use zercalo_format::scene::{
-    Camera, HasBounding, HasCamera, HasMutCamera, HasScene, Light, Model, Scene,
+    Aabb, Camera, HasBounding, HasCamera, HasModels, HasMutCamera, HasScene, Light, Model, Scene,
 };
 
 pub struct HarvesterScene {
-    track_right: Switcher<Model>,
-    track_left: Switcher<Model>,
-    body: Model,
-    collector: Model,
+    unit: Composition<Box<dyn Part>>,
     /// Scene is cached to store voxels for renderer
     rendered: Scene,
 }
@@ -21,22 +22,33 @@ pub fn new_harvester_scene(
 ) -> Result<RotationView<HarvesterScene>, VoxImportError> {
     let mut body = from_vox_file("./assets/models/harvester/harvester_body.vox")?[0].clone();
     body.replace_colors = hashmap! {
-        ColorRGBA::new(183, 183, 183, 255) => player_color,
         ColorRGBA::new(23, 84, 131, 255) => ColorRGBA::new(23, 84, 131, 100),
     };
-    body.offset = Vec3::new(4., 0., 0.);
-
-    let mut track_right = new_track()?;
-    for m in track_right.variants.iter_mut() {
-        m.offset = Vec3::new(0., 0., 4.);
-    }
-    let mut track_left = track_right.clone();
-    for m in track_left.variants.iter_mut() {
-        m.offset = Vec3::new(16.0, 0., 4.);
-    }
+    body.remap_rules = vec![RemapRule::new(
+        ColorMatch::Tolerance {
+            color: ColorRGBA::new(183, 183, 183, 255),
+            tolerance: 8,
+        },
+        ColorTarget::Color(player_color),
+    )];
+    let track = new_track()?;
+    let collector = from_vox_file("./assets/models/harvester/harvester_collector.vox")?[0].clone();
 
-    let mut collector = from_vox_file("./assets/models/harvester/harvester_collector.vox")?[0].clone();
-    collector.offset = Vec3::new(0.0, 0.0, 32.0);
+    // Parts are placed relative to the body
+    let unit = Composition::new()
+        .with_transform(Vec3::new(4., 0., 0.), Quat::IDENTITY)
+        .with_part(Box::new(body) as Box<dyn Part>, Vec3::ZERO, Quat::IDENTITY)
+        .with_part(
+            // Game plays track sound when the track cycle starts
+            Box::new(Marked::new(
+                track.clone(),
+                vec![Marker::new("track_cycle", 0).with_payload("left_track")],
+            )),
+            Vec3::new(-4., 0., 4.),
+            Quat::IDENTITY,
+        )
+        .with_part(Box::new(track), Vec3::new(12., 0., 4.), Quat::IDENTITY)
+        .with_part(Box::new(collector), Vec3::new(-4., 0., 32.), Quat::IDENTITY);
 
     let eye = Vec3::new(128., 128., 128.);
     let scene = Scene {
@@ -56,33 +68,35 @@ pub fn new_harvester_scene(
         ..Scene::default()
     };
     let ext_scene = HarvesterScene {
-        track_right,
-        track_left,
-        body,
-        collector,
+        unit,
         rendered: scene,
     };
-    Ok(RotationView {
-        scene: ext_scene,
-        target_y: Some(8.0),
-        rotation_speed: std::f32::consts::PI / 180.0,
-    })
+    Ok(RotationView::new(
+        ext_scene,
+        Some(8.0),
+        std::f32::consts::PI / 180.0,
+    ))
 }
 
-fn new_track() -> Result<Switcher<Model>, VoxImportError> {
-    let paths = vec![
-        (5, "./assets/models/harvester/harvester_track_01.vox"),
-        (5, "./assets/models/harvester/harvester_track_02.vox"),
-        (5, "./assets/models/harvester/harvester_track_03.vox"),
-    ];
-    let models: Result<Vec<(u32, Model)>, VoxImportError> = paths
-        .into_iter()
-        .map(|(dur, path)| {
-            from_vox_file(path)
-                .map(|vs| (dur, vs.into_iter().next().expect("Zero models in vox file")))
+/// Clips of the harvester seen from fixed angle: standing still and moving with running tracks
+pub fn new_harvester_clips(
+    player_color: ColorRGBA,
+) -> Result<Vec<Clip<RotationView<HarvesterScene>>>, VoxImportError> {
+    let move_frames = new_track()?.cycle_len();
+    let fixed_view = || {
+        new_harvester_scene(player_color).map(|mut view| {
+            view.rotation_speed = 0.0;
+            view
         })
-        .collect();
-    Ok(Switcher::new(models?))
+    };
+    Ok(vec![
+        Clip::new("idle", 1, LoopMode::Once, fixed_view()?),
+        Clip::new("move", move_frames, LoopMode::Loop, fixed_view()?),
+    ])
+}
+
+fn new_track() -> Result<Switcher<Model>, VoxImportError> {
+    from_vox_sequence("./assets/models/harvester/harvester_track_*.vox", 5)
 }
 
 impl HasCamera for HarvesterScene {
@@ -104,20 +118,26 @@ impl HasScene for HarvesterScene {
 }
 
 impl Animatable for HarvesterScene {
-    fn animate(&mut self, frame: u32) {
-        self.track_right.animate(frame);
-        self.track_left.animate(frame);
-        self.rendered.models = vec![
-            self.track_right.current().clone(),
-            self.track_left.current().clone(),
-            self.body.clone(),
-            self.collector.clone(),
-        ];
+    fn evaluate(&mut self, frame: u32) {
+        self.unit.evaluate(frame);
+        self.rendered.models = self.unit.get_models();
     }
 }
 
 impl HasBounding for HarvesterScene {
-    fn get_bounding_volume(&self) -> (Vec3, Vec3) {
+    fn get_bounding_volume(&self) -> Aabb {
         self.rendered.bounding()
     }
 }
+
+impl HasMarkers for HarvesterScene {
+    fn get_markers(&self, frames: Range<u32>) -> Vec<Marker> {
+        self.unit.get_markers(frames)
+    }
+}
+
+impl HasCycle for HarvesterScene {
+    fn cycle_frames(&self) -> Option<u32> {
+        self.unit.cycle_frames()
+    }
+}
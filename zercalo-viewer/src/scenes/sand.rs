@@ -1,8 +1,10 @@
 use glam::{UVec2, UVec3, Vec2, Vec3};
-use zercalo_format::animation::{Animatable, RotationView};
+use zercalo_format::animation::{Animatable, HasCycle, RotationView};
 use zercalo_format::color::{ColorRGB, ColorRGBA};
 use zercalo_format::procedure::particles::ParticlesModel;
-use zercalo_format::scene::{Camera, HasBounding, HasCamera, HasMutCamera, HasScene, Light, Scene};
+use zercalo_format::scene::{
+    Aabb, Camera, HasBounding, HasCamera, HasMutCamera, HasScene, Light, Scene,
+};
 
 pub struct SandScene {
     sand: ParticlesModel,
@@ -50,12 +52,8 @@ impl SandScene {
             sand: model,
             rendered: scene,
         };
-        sand_scene.animate(0);
-        RotationView {
-            scene: sand_scene,
-            target_y: Some(0.0),
-            rotation_speed: 0.0,
-        } // std::f32::consts::PI / 180.0
+        sand_scene.evaluate(0);
+        RotationView::new(sand_scene, Some(0.0), 0.0) // std::f32::consts::PI / 180.0
     }
 }
 
@@ -78,14 +76,20 @@ impl HasScene for SandScene {
 }
 
 impl Animatable for SandScene {
-    fn animate(&mut self, frame: u32) {
-        self.sand.animate(frame);
+    fn evaluate(&mut self, frame: u32) {
+        self.sand.evaluate(frame);
         self.rendered.models = vec![self.sand.generate()];
     }
 }
 
 impl HasBounding for SandScene {
-    fn get_bounding_volume(&self) -> (Vec3, Vec3) {
+    fn get_bounding_volume(&self) -> Aabb {
         self.rendered.bounding()
     }
 }
+
+impl HasCycle for SandScene {
+    fn cycle_frames(&self) -> Option<u32> {
+        self.sand.cycle_frames()
+    }
+}
@@ -1,45 +1,58 @@
 use glam::{UVec2, Vec2, Vec3};
-use log::*;
-use zercalo_format::animation::{Animatable, RotationView, Stepper, Switcher};
+use zercalo_format::animation::{
+    Animatable, Clip, Easing, HasCycle, LoopMode, RotationView, SceneTrack, Sequence, Switcher,
+    Track, Tracked,
+};
 use zercalo_format::color::ColorRGB;
-use zercalo_format::import::vox::{from_vox_file, VoxImportError};
+use zercalo_format::import::vox::{from_vox_file, from_vox_sequence, VoxImportError};
 use zercalo_format::scene::{
-    Camera, HasBounding, HasCamera, HasMutCamera, HasScene, Light, Model, Scene,
+    Aabb, Camera, HasBounding, HasCamera, HasMutCamera, HasMutScene, HasScene, Light, Model, Scene,
 };
 
 pub struct SandWormScene {
-    body: Switcher<Stepper<Switcher<Model>>>,
+    body: Switcher<Model>,
     /// Scene is cached to store voxels for renderer
     rendered: Scene,
 }
 
-pub fn new_sandworm_scene() -> Result<RotationView<SandWormScene>, VoxImportError> {
+/// One movement of the worm, its body animation with the offset track
+type SandWormMove = Tracked<SandWormScene>;
+
+/// Worm rises from the sand and sinks back, both movements are played in sequence as looped clip
+pub fn new_sandworm_scene(
+) -> Result<RotationView<Clip<Sequence<SandWormMove, SandWormMove>>>, VoxImportError> {
     let zstep = 0.4;
     let zstart = -12.0;
     let ascending = make_body_ascending()?;
     let ascending_cycle = ascending.cycle_len();
-    let stage0 = Stepper::new(ascending, move |m, frames| {
-        let i = frames % ascending_cycle;
-        debug!(
-            "Stage0 stepper frame {}, internal frame {}, active model {}",
-            frames, i, m.active
-        );
-        m.current_mut().offset = Vec3::new(0., i as f32 * zstep + zstart, 0.);
-    });
-
     let descending = make_body_descending()?;
     let descending_cycle = descending.cycle_len();
     let zend = ascending_cycle as f32 * zstep + zstart;
-    let stage1 = Stepper::new(descending, move |m, frames| {
-        let i = (frames - ascending_cycle) % descending_cycle;
-        debug!(
-            "Stage1 stepper frame {}, internal frame {}, active model {}",
-            frames, i, m.active
-        );
-        m.current_mut().offset = Vec3::new(0., zend - i as f32 * zstep, 0.);
-    });
+    let zbottom = zend - descending_cycle as f32 * zstep;
 
-    let body = Switcher::new(vec![(ascending_cycle, stage0), (descending_cycle, stage1)]);
+    let body = Sequence::new(
+        new_move(ascending, zstart, zend),
+        ascending_cycle,
+        new_move(descending, zend, zbottom),
+    );
+    let clip = Clip::new(
+        "sandworm",
+        ascending_cycle + descending_cycle,
+        LoopMode::Loop,
+        body,
+    );
+    Ok(RotationView::new(
+        clip,
+        Some(10.0),
+        std::f32::consts::PI / 180.0,
+    ))
+}
+
+/// Body moving vertically from `zfrom` to `zto` during its cycle
+fn new_move(body: Switcher<Model>, zfrom: f32, zto: f32) -> SandWormMove {
+    let offset = Track::new()
+        .with_key(0, Vec3::new(0., zfrom, 0.), Easing::Linear)
+        .with_key(body.cycle_len(), Vec3::new(0., zto, 0.), Easing::Linear);
     let eye = Vec3::new(128., 128., 128.);
     let scene = Scene {
         camera: Camera {
@@ -61,30 +74,11 @@ pub fn new_sandworm_scene() -> Result<RotationView<SandWormScene>, VoxImportErro
         body,
         rendered: scene,
     };
-    Ok(RotationView {
-        scene: ext_scene,
-        target_y: Some(10.0),
-        rotation_speed: std::f32::consts::PI / 180.0,
-    })
+    Tracked::new(ext_scene, vec![SceneTrack::ModelOffset(0, offset)])
 }
 
 fn make_body_ascending() -> Result<Switcher<Model>, VoxImportError> {
-    let paths = vec![
-        (5, "./assets/models/sandworm/worm_01.vox"),
-        (5, "./assets/models/sandworm/worm_02.vox"),
-        (5, "./assets/models/sandworm/worm_03.vox"),
-        (5, "./assets/models/sandworm/worm_04.vox"),
-        (5, "./assets/models/sandworm/worm_05.vox"),
-        (5, "./assets/models/sandworm/worm_06.vox"),
-    ];
-    let models: Result<Vec<(u32, Model)>, VoxImportError> = paths
-        .into_iter()
-        .map(|(dur, path)| {
-            from_vox_file(path)
-                .map(|vs| (dur, vs.into_iter().next().expect("Zero models in vox file")))
-        })
-        .collect();
-    Ok(Switcher::new(models?))
+    from_vox_sequence("./assets/models/sandworm/worm_*.vox", 5)
 }
 
 fn make_body_descending() -> Result<Switcher<Model>, VoxImportError> {
@@ -115,15 +109,27 @@ impl HasScene for SandWormScene {
     }
 }
 
+impl HasMutScene for SandWormScene {
+    fn get_scene_mut(&mut self) -> &mut Scene {
+        &mut self.rendered
+    }
+}
+
 impl Animatable for SandWormScene {
-    fn animate(&mut self, frame: u32) {
-        self.body.animate(frame);
-        self.rendered.models = vec![self.body.current().value.current().clone()];
+    fn evaluate(&mut self, frame: u32) {
+        self.body.evaluate(frame);
+        self.rendered.models = vec![self.body.current().clone()];
     }
 }
 
 impl HasBounding for SandWormScene {
-    fn get_bounding_volume(&self) -> (Vec3, Vec3) {
+    fn get_bounding_volume(&self) -> Aabb {
         self.rendered.bounding()
     }
 }
+
+impl HasCycle for SandWormScene {
+    fn cycle_frames(&self) -> Option<u32> {
+        self.body.cycle_frames()
+    }
+}
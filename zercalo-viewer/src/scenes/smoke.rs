@@ -1,10 +1,12 @@
 use glam::f32::Quat;
 use glam::{UVec2, UVec3, Vec3};
 use noise::OpenSimplex;
-use zercalo_format::animation::{Animatable, RotationView};
-use zercalo_format::color::{ColorRGB, ColorRGBA};
-use zercalo_format::procedure::smoke::{SmokeModel, SmokePart};
-use zercalo_format::scene::{Camera, HasBounding, HasCamera, HasMutCamera, HasScene, Light, Scene};
+use zercalo_format::animation::{Animatable, HasCycle, RotationView};
+use zercalo_format::color::ColorRGB;
+use zercalo_format::procedure::smoke::{default_heat_colors, SmokeModel, SmokePart};
+use zercalo_format::scene::{
+    Aabb, Camera, HasBounding, HasCamera, HasMutCamera, HasScene, Light, Scene,
+};
 
 pub struct SmokeScene {
     smoke: SmokeModel,
@@ -33,12 +35,8 @@ impl SmokeScene {
                 ..Scene::default()
             },
         };
-        scene.animate(0);
-        RotationView {
-            scene,
-            target_y: Some(32.0),
-            rotation_speed: 0.0,
-        } // std::f32::consts::PI / 180.0
+        scene.evaluate(0);
+        RotationView::new(scene, Some(32.0), 0.0) // std::f32::consts::PI / 180.0
     }
 
     fn smoke_model() -> SmokeModel {
@@ -110,11 +108,10 @@ impl SmokeScene {
             rotation: Quat::from_axis_angle(Vec3::Y, 0.0),
             particles,
             noise: OpenSimplex::new(),
-            cold_color: ColorRGBA::new(111, 123, 155, 255),
-            hot_color: ColorRGBA::new(229, 88, 41, 255),
-            very_hot_color: ColorRGBA::new(249, 195, 0, 255),
+            heat_colors: default_heat_colors(),
             ceiling_height: 42.0,
             ceiling_speed: -0.1,
+            ..SmokeModel::default()
         }
     }
 }
@@ -138,14 +135,20 @@ impl HasScene for SmokeScene {
 }
 
 impl Animatable for SmokeScene {
-    fn animate(&mut self, frame: u32) {
-        self.smoke.animate(frame);
+    fn evaluate(&mut self, frame: u32) {
+        self.smoke.evaluate(frame);
         self.rendered.models = vec![self.smoke.generate()];
     }
 }
 
 impl HasBounding for SmokeScene {
-    fn get_bounding_volume(&self) -> (Vec3, Vec3) {
+    fn get_bounding_volume(&self) -> Aabb {
         self.rendered.bounding()
     }
 }
+
+impl HasCycle for SmokeScene {
+    fn cycle_frames(&self) -> Option<u32> {
+        self.smoke.cycle_frames()
+    }
+}
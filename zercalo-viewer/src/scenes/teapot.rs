@@ -26,9 +26,9 @@ pub fn new_teapot_scene() -> Result<TeapotScene, VoxImportError> {
         models: vec![model[0].clone()],
         ..Scene::default()
     };
-    Ok(RotationView {
+    Ok(RotationView::new(
         scene,
-        target_y: Some(32.0),
-        rotation_speed: std::f32::consts::PI / 180.0,
-    })
+        Some(32.0),
+        std::f32::consts::PI / 180.0,
+    ))
 }